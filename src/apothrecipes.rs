@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::io::Cursor;
 use std::path::PathBuf;
 use std::error::Error;
 
@@ -6,6 +8,8 @@ use strum::EnumString;
 
 use crate::config::AppConfig;
 
+const AILMENT_LOOKUP_TXT: &str = include_str!("../saveedit_data/AilmentLookup.txt");
+
 #[derive(Debug, Clone)]
 struct ApothDataError;
 
@@ -26,12 +30,21 @@ pub enum CureResultType {
     Placebo = 4,
 }
 
+/// Worst cure quality an item is known to be produced as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CureStatus {
+    FullCure,
+    PartialCure,
+    FailureOrPlacebo,
+    SideEffect,
+}
+
 #[derive(Debug, Clone)]
 pub struct ApothRecipe {
-    result_type: CureResultType,
-    ailment_id: i32,
-    item_id: i32,
-    item_id_with_side_effects: i32,
+    pub result_type: CureResultType,
+    pub ailment_id: i32,
+    pub item_id: i32,
+    pub item_id_with_side_effects: i32,
 }
 
 pub struct ApothRecipeManager {
@@ -40,6 +53,9 @@ pub struct ApothRecipeManager {
     pub doc_el: Option<xot::Node>,
     pub apothrecipes_node: Option<xot::Node>,
     pub all_cures: Vec<ApothRecipe>,
+    pub ailment_lookup: HashMap<i32, String>,
+    /// worst cure status of each item made by a recipe, built when the recipes load
+    pub cure_statuses: HashMap<i32, CureStatus>,
 }
 
 impl Default for ApothRecipeManager {
@@ -50,6 +66,8 @@ impl Default for ApothRecipeManager {
             doc_el: None,
             apothrecipes_node: None,
             all_cures: Vec::new(),
+            ailment_lookup: HashMap::new(),
+            cure_statuses: HashMap::new(),
         }
     }
 }
@@ -73,13 +91,40 @@ impl ApothRecipeManager {
     }
 
     pub fn is_not_full_cure_id(&self, uid: i32) -> bool {
-        let mut known_duds: Vec<i32> = Vec::new();
+        self.get_cure_status(uid).is_some_and(|x| x != CureStatus::FullCure)
+    }
+
+    /// Display name for an ailment, falls back to the raw id if it isn't in the lookup.
+    pub fn get_ailment_name(&self, ailment_id: i32) -> String {
+        match self.ailment_lookup.get(&ailment_id) {
+            Some(name) => name.clone(),
+            None => format!("Ailment {}", ailment_id),
+        }
+    }
+
+    /// All recipes where uid is the result, or the side effect result.
+    pub fn get_recipes_for_item(&self, uid: i32) -> Vec<&ApothRecipe> {
+        self.all_cures.iter().filter(|x| x.item_id == uid || x.item_id_with_side_effects == uid).collect()
+    }
 
-        known_duds.extend(self.get_partial_cure_ids().iter());
-        known_duds.extend(self.get_failure_or_placebo_cure_ids().iter());
-        known_duds.extend(self.get_sideeffect_cure_ids().iter());
+    pub fn get_cure_status(&self, uid: i32) -> Option<CureStatus> {
+        self.cure_statuses.get(&uid).copied()
+    }
 
-        known_duds.contains(&uid)
+    /// Fills cure_statuses from all_cures, the worst status winning: side effect, then failure or placebo, partial, full.
+    fn build_cure_statuses(&mut self) {
+        self.cure_statuses.clear();
+        let statuses = [
+            (CureStatus::FullCure, self.get_full_cure_ids()),
+            (CureStatus::PartialCure, self.get_partial_cure_ids()),
+            (CureStatus::FailureOrPlacebo, self.get_failure_or_placebo_cure_ids()),
+            (CureStatus::SideEffect, self.get_sideeffect_cure_ids()),
+        ];
+        for (cure_status, uids) in statuses {
+            for uid in uids {
+                self.cure_statuses.insert(uid, cure_status);
+            };
+        };
     }
 
     pub fn clear_data(&mut self) {
@@ -87,43 +132,28 @@ impl ApothRecipeManager {
         self.doc_el = None;
         self.apothrecipes_node = None;
         self.all_cures.clear();
+        self.ailment_lookup.clear();
+        self.cure_statuses.clear();
         self.xtree = xot::Xot::new();
     }
 
     pub fn load_data(&mut self, appconfig: &AppConfig) -> Result<(), Box<dyn Error>> {
 
-        let filepath_apothrecipes = PathBuf::from_iter([&appconfig.path_kynseed_data, &appconfig.filename_kynseed_apothrecipes]);
-
-        let mut xml_vec = std::fs::read(&filepath_apothrecipes)?;
-
-        // remove BOM
-        if xml_vec[0..3] == [b'\xef', b'\xbb', b'\xbf'] {
-            xml_vec.remove(0);
-            xml_vec.remove(0);
-            xml_vec.remove(0);
-        };
-        // should fix in xot crate
-        for i in 0..50 {
-            // dbg!(xml_vec[i], xml_vec[i+1], xml_vec[i+2], xml_vec[i+3], xml_vec[i+4]);
-            if xml_vec[i] as char == 'u' 
-                && xml_vec[i+1] as char == 't' 
-                && xml_vec[i+2] as char == 'f' 
-                && xml_vec[i+3] as char == '-' 
-                && xml_vec[i+4] as char == '8' 
-                {
-                    xml_vec[i] = b'U';
-                    xml_vec[i+1] = b'T';
-                    xml_vec[i+2] = b'F';
-                    break;
-            };
+        // the embedded lookup ships empty, so the file on disk is read whenever it's there
+        let mut filepath_ailment_lookup: PathBuf = PathBuf::from("fake_path");
+        if !appconfig.b_use_embedded_saveedit_data || AILMENT_LOOKUP_TXT.trim().is_empty() {
+            filepath_ailment_lookup = PathBuf::from_iter([&appconfig.path_saveedit_data, &appconfig.filename_saveedit_ailment_lookup]);
         };
+        self.load_ailment_lookup(&filepath_ailment_lookup)?;
+
+        let filepath_apothrecipes = PathBuf::from_iter([&appconfig.path_kynseed_data, &appconfig.filename_kynseed_apothrecipes]);
 
-        let xml_str = std::str::from_utf8(&xml_vec)?;
+        let xml_str = read_kynseed_xml(&filepath_apothrecipes)?;
 
         // should fix in xot crate
         // self.root = Some(self.xtree.parse(&xml_str.replacen("utf-8", "UTF-8", 1)).unwrap());
 
-        self.root = Some(self.xtree.parse(xml_str)?);
+        self.root = Some(self.xtree.parse(&xml_str)?);
         self.doc_el = match self.xtree.document_element(self.root.unwrap()) {
             Ok(x) => Some(x),
             Err(e) => return Err(Box::new(e))
@@ -163,9 +193,40 @@ impl ApothRecipeManager {
             self.all_cures.push(ApothRecipe{result_type, ailment_id, item_id, item_id_with_side_effects});
 
         };
+        self.build_cure_statuses();
+
         Ok(())
     }
 
+    pub fn load_ailment_lookup(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        match file_path.is_file() {
+            true => {
+                let mut rdr = csv::ReaderBuilder::new()
+                    .delimiter(b'|')
+                    .has_headers(false)
+                    .from_path(file_path)?;
+                self.ailment_lookup.clear();
+                for result in rdr.deserialize() {
+                    let record: (String, i32) = result?;
+                    self.ailment_lookup.insert(record.1, record.0);
+                };
+                Ok(())
+            }
+            false => {
+                let mut rdr = csv::ReaderBuilder::new()
+                    .delimiter(b'|')
+                    .has_headers(false)
+                    .from_reader(Cursor::new(AILMENT_LOOKUP_TXT));
+                self.ailment_lookup.clear();
+                for result in rdr.deserialize() {
+                    let record: (String, i32) = result?;
+                    self.ailment_lookup.insert(record.1, record.0);
+                };
+                Ok(())
+            }
+        }
+    }
+
     pub fn get_name_from_node(&self, node: xot::Node) -> Option<&str> {
         let node_el_result = self.xtree.element(node);
         let node_el_name: &str;
@@ -195,4 +256,23 @@ impl ApothRecipeManager {
         };
         None
     }
-}
\ No newline at end of file
+}
+
+/// Reads a game xml file, stripping the BOM and upper-casing the utf-8 encoding declaration so xot will parse it.
+pub fn read_kynseed_xml(file_path: &PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut xml_vec = std::fs::read(file_path)?;
+
+    // remove BOM
+    if xml_vec.len() >= 3 && xml_vec[0..3] == [b'\xef', b'\xbb', b'\xbf'] {
+        xml_vec.drain(0..3);
+    };
+    // should fix in xot crate
+    for i in 0..50.min(xml_vec.len().saturating_sub(4)) {
+        if &xml_vec[i..i+5] == b"utf-8" {
+            xml_vec[i..i+3].copy_from_slice(b"UTF");
+            break;
+        };
+    };
+
+    Ok(String::from_utf8(xml_vec)?)
+}
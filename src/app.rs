@@ -1,17 +1,36 @@
-use std::hash::{Hash, Hasher};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::io::Write;
 
+use strum::{EnumIter, IntoEnumIterator};
+
 
 use crate::config;
 use crate::lootitems;
 use crate::savedata;
 use crate::apothrecipes;
+use crate::refdata;
+use crate::savesearch;
+use crate::savechanges::{SaveChangeEvent, SaveChangeListener, SaveChanges};
+use crate::savesections;
+use crate::savesurvey;
 
 pub struct ShowUIState {
     loot_ref_window: bool,
     loot_ref_name_filter: String,
     loot_ref_type_filter: String,
+    loot_ref_star_rating_filter: Option<bool>,
+    loot_ref_liquid_filter: Option<bool>,
+    loot_ref_hide_quantity_filter: Option<bool>,
+    loot_ref_carryable_filter: Option<bool>,
+    loot_ref_cure_filter: Option<apothrecipes::CureStatus>,
+    loot_ref_sort_column: LootRefColumn,
+    loot_ref_sort_descending: bool,
+    loot_ref_selected_uid: Option<i32>,
+    loot_ref_view: LootRefView,
+    apoth_ref_window: bool,
+    apoth_ref_ailment_filter: String,
+    star_rating_report_window: bool,
     options_window: bool,
     top_panel: bool,
     central_panel: bool,
@@ -20,6 +39,19 @@ pub struct ShowUIState {
     player_data_window: bool,
 
     save_tree_window: bool,
+    save_tree_view_state: SaveTreeViewState,
+    quick_edits_window: bool,
+
+    save_overview_window: bool,
+    save_overview_category: Option<savesurvey::SurveyCategory>,
+    b_save_overview_hide_edited: bool,
+    save_survey: savesurvey::SaveSurvey,
+    /// names of the open SectionPlacement::Window sections
+    section_windows: HashSet<&'static str>,
+
+    /// profiles in the config file, for the Options window
+    profile_names: Vec<String>,
+    new_profile_name: String,
 }
 
 impl Default for ShowUIState {
@@ -28,6 +60,18 @@ impl Default for ShowUIState {
             loot_ref_window: false,
             loot_ref_name_filter: "".to_string(),
             loot_ref_type_filter: "".to_string(),
+            loot_ref_star_rating_filter: None,
+            loot_ref_liquid_filter: None,
+            loot_ref_hide_quantity_filter: None,
+            loot_ref_carryable_filter: None,
+            loot_ref_cure_filter: None,
+            loot_ref_sort_column: LootRefColumn::Uid,
+            loot_ref_sort_descending: false,
+            loot_ref_selected_uid: None,
+            loot_ref_view: LootRefView::default(),
+            apoth_ref_window: false,
+            apoth_ref_ailment_filter: "".to_string(),
+            star_rating_report_window: false,
             options_window: false,
             top_panel: true,
             central_panel: true,
@@ -36,44 +80,136 @@ impl Default for ShowUIState {
             player_data_window: false,
 
             save_tree_window: false,
+            save_tree_view_state: SaveTreeViewState::default(),
+            quick_edits_window: false,
+
+            save_overview_window: false,
+            save_overview_category: None,
+            b_save_overview_hide_edited: true,
+            save_survey: savesurvey::SaveSurvey::default(),
+            section_windows: HashSet::new(),
+
+            profile_names: Vec::new(),
+            new_profile_name: "".to_string(),
         }
     }
 }
 
+/// Search, filter and selection state of the save tree window.
 #[derive(Default)]
-pub struct PlayerData {
-    brass: u32,
-    stats: Vec<(usize, String, u8)>,
-    tool_level: Vec<(usize, String, u8, f32)>,
+pub struct SaveTreeViewState {
+    search_query: String,
+    search_scope: savesearch::SaveTreeSearchScope,
+    search_results: Vec<savesearch::SaveTreeSearchResult>,
+    matched_nodes: HashSet<xot::Node>,
+    /// Some when the tree is filtered down to the search results
+    visible_nodes: Option<HashSet<xot::Node>>,
+    b_filter_tree: bool,
+    selected_node: Option<xot::Node>,
+    b_scroll_to_selected: bool,
+    query: String,
+    query_set_value: String,
+    query_error: String,
+    /// name used by the rename, insert and wrap actions
+    edit_name: String,
+    edit_attribute_name: String,
+    edit_attribute_value: String,
+    xml_file_path: String,
+    paste_xml: String,
+    /// node to add to the bookmarks once the tree is no longer borrowed
+    bookmark_request: Option<xot::Node>,
+    /// value types of the leaves shown by the value editors, here and in the other windows
+    value_types: savedata::SaveValueTypeCache,
+}
+
+impl SaveTreeViewState {
+
+    pub fn collapsing_id(node: xot::Node) -> egui::Id {
+        egui::Id::new(("save_tree", node))
+    }
+
+    pub fn run_search(&mut self, tree: &savedata::SaveNodeTree, xtree: &xot::Xot) {
+        self.search_results.clear();
+        self.matched_nodes.clear();
+        self.visible_nodes = None;
+        self.query_error.clear();
+        if self.search_query.trim().is_empty() {return};
+
+        let search = match savesearch::SaveTreeSearch::parse(&self.search_query) {
+            Ok(x) => x,
+            Err(e) => {
+                self.query_error = e.to_string();
+                return;
+            },
+        };
+        self.search_results = search.search(&self.search_scope, tree.0, xtree);
+        self.matched_nodes = self.search_results.iter().map(|x| x.node).collect();
+        if self.b_filter_tree {
+            self.visible_nodes = Some(savesearch::get_visible_nodes(&self.search_results, xtree));
+        };
+    }
+
+    /// Shows the nodes matching the save query as the search results.
+    pub fn run_query(&mut self, sm: &savedata::SaveDataManager) {
+        self.search_results.clear();
+        self.matched_nodes.clear();
+        self.visible_nodes = None;
+        self.query_error.clear();
+        if self.query.trim().is_empty() {return};
+
+        match sm.query(&self.query) {
+            Ok(nodes) => {
+                self.search_results = savedata::SaveDataManager::get_node_paths_xt(&sm.xtree, &nodes).into_iter()
+                    .zip(nodes.iter())
+                    .map(|(path, x)| savesearch::SaveTreeSearchResult {
+                        node: *x,
+                        path,
+                        text: sm.xtree.text_content_str(*x).unwrap_or("").to_string(),
+                    })
+                    .collect();
+                self.matched_nodes = nodes.into_iter().collect();
+                if self.b_filter_tree {
+                    self.visible_nodes = Some(savesearch::get_visible_nodes(&self.search_results, &sm.xtree));
+                };
+            },
+            Err(e) => self.query_error = e.to_string(),
+        };
+    }
+
+    /// Selects node, opens all its ancestors in the tree and scrolls to it on the next frame.
+    pub fn select_and_expand(&mut self, ctx: &egui::Context, xtree: &xot::Xot, node: xot::Node) {
+        for ancestor in xtree.ancestors(node) {
+            let mut state = egui::collapsing_header::CollapsingState::load_with_default_open(ctx, Self::collapsing_id(ancestor), false);
+            state.set_open(true);
+            state.store(ctx);
+        };
+        self.selected_node = Some(node);
+        self.b_scroll_to_selected = true;
+    }
 }
 
 pub struct App {
     appconfig : config::AppConfig,
     lm: lootitems::LootManager,
     sm: savedata::SaveDataManager,
-    save_inventory_items: Vec<AppSaveInventoryItem>,
+    sections: savesections::SaveSectionRegistry,
     arm: apothrecipes::ApothRecipeManager,
+    rd: refdata::RefDataManager,
     show_ui_state: ShowUIState,
-    player_data: PlayerData,
 }
 
 impl App {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let mut show_ui_state = ShowUIState::default();
-
-        let config_filepath = config::get_config_filepath();
-        let appconfig: config::AppConfig = match confy::load_path(config_filepath.as_path()) {
-            Ok(cfg) => cfg,
-            Err(_e) => {
-                confy::store_path(config_filepath.as_path(), config::AppConfig::default()).unwrap();
-                config::AppConfig::default()
-            }
-        };
+    pub fn new(_cc: &eframe::CreationContext<'_>, appconfig: config::AppConfig) -> Self {
+        let mut show_ui_state = ShowUIState { profile_names: config::load_config_file().map(|x| x.profile_names()).unwrap_or_default(), ..Default::default() };
 
         let mut lm = lootitems::LootManager::default();
         lm.clear_data();
         match lm.load_data(&appconfig) {
-            Ok(..) => {},
+            Ok(..) => {
+                if !lm.load_warnings.is_empty() {
+                    show_ui_state.error_msg.push_str(&format!("Loot data warnings: {}. ", lm.load_warnings.join("; ")));
+                };
+            },
             _ => {
                 lm.clear_data();
                 show_ui_state.error_msg.push_str("Unable to load loot data."); 
@@ -92,123 +228,86 @@ impl App {
             } 
         };
 
-        let mut save_inventory_items: Vec<AppSaveInventoryItem>  = Vec::new();
-        let mut player_data: PlayerData = PlayerData::default();
-
-        match show_ui_state.error_during_load {
-            true => {
-                show_ui_state.options_window = true;
-            },
-            false => {
-                for siir in sm.save_inventory_ref.iter() {
-                    let sii = AppSaveInventoryItem::new(siir, &sm, &lm);
-                    save_inventory_items.push(sii);
-                };
-        
-                save_inventory_items.sort_by(|a,b| 
-                    {let first = a.pickup_type_name.cmp(&b.pickup_type_name);
-                    let second = a.name.cmp(&b.name);
-                    first.then(second)}
-                );
-
-                player_data.brass = {
-                    match sm.xtree.text_content_str(sm.brass_count_node.unwrap()) {
-                        Some(x) => x.parse().unwrap_or_else(|_| {
-                            sm.clear_data();
-                            show_ui_state.error_msg.push_str("Unable to load save data. Brass."); 
-                            show_ui_state.error_during_load = true;
-                            0
-                            }),
-                        _ => {
-                            sm.clear_data();
-                            show_ui_state.error_msg.push_str("Unable to load save data. Brass."); 
-                            show_ui_state.error_during_load = true;
-                            0
-                        }
-                    }
-                };
-
-                for (idx, item) in sm.stats_nodes.iter().enumerate() {
-                    let stat_name = sm.get_name_from_node(*item);
-                    let stat_val_str = sm.xtree.text_content_str(*item);
-                    if stat_name.is_some() && stat_val_str.is_some() {
-                        let stat_val: u8 = stat_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                        player_data.stats.push((idx, stat_name.unwrap().to_string(), stat_val));
-                    }
-                };
-
-                for (idx, item) in sm.tool_level_ref.iter().enumerate() {
-                    let tool_name = sm.xtree.text_content_str(item.tool_type_node);
-                    let tool_level_val_str = sm.xtree.text_content_str(item.tool_level_node);
-                    let tool_xp_val_str = sm.xtree.text_content_str(item.tool_current_xp_node);
-                    if tool_name.is_some() && tool_level_val_str.is_some() && tool_xp_val_str.is_some() {
-                        let tool_level_val: u8 = tool_level_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                        let tool_xp_val: f32 = tool_xp_val_str.unwrap().parse().unwrap_or_else(|_| {0.0});
-                        player_data.tool_level.push((idx, tool_name.unwrap().to_string(), tool_level_val, tool_xp_val));
-                    }
-                };
-            }
-        }
-
         let mut arm = apothrecipes::ApothRecipeManager::default();
         match arm.load_data(&appconfig) {
             Ok(..) => {},
-            _ => {
+            Err(e) => {
                 arm.clear_data();
-                show_ui_state.error_msg.push_str("Unable to load apoth recipe data, not blocking.");
+                show_ui_state.error_msg.push_str(&format!("Unable to load apoth recipe data, not blocking. {}.", e));
             } 
         }
 
+        let mut rd = refdata::RefDataManager::default();
+        if let Err(e) = rd.load_data(&appconfig) {
+            rd.clear_data();
+            show_ui_state.error_msg.push_str(&format!("Unable to load reference data, not blocking. {}.", e));
+        };
+
+        let mut sections = savesections::SaveSectionRegistry::default();
+        if !show_ui_state.error_during_load {
+            let sc = savesections::SectionContext { appconfig: &appconfig, lm: &lm, arm: &arm, rd: &rd };
+            if let Err(e) = sections.load_data(&mut sm, &sc) {
+                show_ui_state.error_msg.push_str(&format!("Unable to load save data. {}.", e));
+                show_ui_state.error_during_load = true;
+            };
+        };
+
         if show_ui_state.error_during_load { 
-            save_inventory_items.clear();
+            show_ui_state.options_window = true;
+            sections.clear_data();
             sm.clear_data();
             lm.clear_data();
             arm.clear_data();
-            player_data = PlayerData::default();
+            rd.clear_data();
         };
 
         Self {
             appconfig,
             lm,
             sm,
-            save_inventory_items,
+            sections,
             arm,
+            rd,
             show_ui_state,
-            player_data,
         }
     }
 
     pub fn reload_data_helper(&mut self) {
-        Self::reload_data(&mut self.appconfig, &mut self.lm, &mut self.sm, &mut self.save_inventory_items, 
-            &mut self.arm, &mut self.show_ui_state.error_during_load, &mut self.show_ui_state.error_msg, 
-            &mut self.player_data);
+        Self::reload_data(&mut self.appconfig, &mut self.lm, &mut self.sm, &mut self.sections, 
+            &mut self.arm, &mut self.rd, &mut self.show_ui_state.error_during_load, &mut self.show_ui_state.error_msg);
+        self.show_ui_state.save_survey.clear();
+        self.show_ui_state.loot_ref_view.clear();
+        self.show_ui_state.save_tree_view_state.value_types.clear();
     }
 
     pub fn reload_data(appconfig: &mut config::AppConfig, lm: &mut lootitems::LootManager, sm: &mut savedata::SaveDataManager, 
-        save_inventory_items: &mut Vec<AppSaveInventoryItem>, arm: &mut apothrecipes::ApothRecipeManager, 
-        show_ui_state_error_during_load: &mut bool, show_ui_state_error_msg: &mut String,
-        player_data: &mut PlayerData
+        sections: &mut savesections::SaveSectionRegistry, arm: &mut apothrecipes::ApothRecipeManager, 
+        rd: &mut refdata::RefDataManager, show_ui_state_error_during_load: &mut bool, show_ui_state_error_msg: &mut String
     ) {
-        let config_filepath = config::get_config_filepath();
-        *appconfig = match confy::load_path(config_filepath.as_path()) {
-            Ok(cfg) => cfg,
-            Err(_e) => {
-                confy::store_path(config_filepath.as_path(), config::AppConfig::default()).unwrap();
-                config::AppConfig::default()
-            }
-        };
-
         *show_ui_state_error_during_load = false;
         *show_ui_state_error_msg = "".to_string();
 
-        save_inventory_items.clear();
+        // stay on the same profile, unless it was removed from the config file
+        *appconfig = match config::load_appconfig_profile(Some(&appconfig.profile_name)) {
+            Ok(x) => x,
+            Err(e) => {
+                show_ui_state_error_msg.push_str(&format!("{}, using the default profile. ", e));
+                config::load_appconfig_profile(Some(config::DEFAULT_PROFILE_NAME)).unwrap_or_default()
+            },
+        };
+
+        sections.clear_data();
         sm.clear_data();
         lm.clear_data();
         arm.clear_data();
-        *player_data = PlayerData::default();
+        rd.clear_data();
 
         match lm.load_data(&appconfig) {
-            Ok(..) => {},
+            Ok(..) => {
+                if !lm.load_warnings.is_empty() {
+                    show_ui_state_error_msg.push_str(&format!("Loot data warnings: {}. ", lm.load_warnings.join("; ")));
+                };
+            },
             _ => {
                 lm.clear_data();
                 show_ui_state_error_msg.push_str("Unable to load loot data."); 
@@ -225,170 +324,232 @@ impl App {
             } 
         };
 
-        match *show_ui_state_error_during_load {
-            true => {},
-            false => {
-                for siir in sm.save_inventory_ref.iter() {
-                    let sii = AppSaveInventoryItem::new(siir, &sm, &lm);
-                    save_inventory_items.push(sii);
-                };
-        
-                save_inventory_items.sort_by(|a,b| 
-                    {let first = a.pickup_type_name.cmp(&b.pickup_type_name);
-                    let second = a.name.cmp(&b.name);
-                    first.then(second)}
-                );
-
-                player_data.brass = {
-                    match sm.xtree.text_content_str(sm.brass_count_node.unwrap()) {
-                        Some(x) => x.parse().unwrap_or_else(|_| {0}),
-                        _ => {0}
-                    }
-                };
-
-                for (idx, item) in sm.stats_nodes.iter().enumerate() {
-                    let stat_name = sm.get_name_from_node(*item);
-                    let stat_val_str = sm.xtree.text_content_str(*item);
-                    if stat_name.is_some() && stat_val_str.is_some() {
-                        let stat_val: u8 = stat_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                        player_data.stats.push((idx, stat_name.unwrap().to_string(), stat_val));
-                    }
-                };
-
-                for (idx, item) in sm.tool_level_ref.iter().enumerate() {
-                    let tool_name = sm.xtree.text_content_str(item.tool_type_node);
-                    let tool_level_val_str = sm.xtree.text_content_str(item.tool_level_node);
-                    let tool_xp_val_str = sm.xtree.text_content_str(item.tool_current_xp_node);
-                    if tool_name.is_some() && tool_level_val_str.is_some() && tool_xp_val_str.is_some() {
-                        let tool_level_val: u8 = tool_level_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                        let tool_xp_val: f32 = tool_xp_val_str.unwrap().parse().unwrap_or_else(|_| {0.0});
-                        player_data.tool_level.push((idx, tool_name.unwrap().to_string(), tool_level_val, tool_xp_val));
-                    }
-                };
-            }
-        }
-
         match arm.load_data(&appconfig) {
             Ok(..) => {},
-            _ => {
+            Err(e) => {
                 arm.clear_data();
-                show_ui_state_error_msg.push_str("Unable to load apoth recipe data, not blocking.");
+                show_ui_state_error_msg.push_str(&format!("Unable to load apoth recipe data, not blocking. {}.", e));
             } 
         }
 
+        if let Err(e) = rd.load_data(appconfig) {
+            rd.clear_data();
+            show_ui_state_error_msg.push_str(&format!("Unable to load reference data, not blocking. {}.", e));
+        };
+
+        if !*show_ui_state_error_during_load {
+            let sc = savesections::SectionContext { appconfig, lm, arm, rd };
+            if let Err(e) = sections.load_data(sm, &sc) {
+                show_ui_state_error_msg.push_str(&format!("Unable to load save data. {}.", e));
+                *show_ui_state_error_during_load = true;
+            };
+        };
+
         if *show_ui_state_error_during_load { 
-            save_inventory_items.clear();
+            sections.clear_data();
             sm.clear_data();
             lm.clear_data();
             arm.clear_data();
-            *player_data = PlayerData::default();
         };
     }
 
-    pub fn save_tree_child_ui(ui: &mut egui::Ui, item: &mut savedata::SaveNodeTree, xtree: &mut xot::Xot,
-        siir: &mut Vec<AppSaveInventoryItem>, lm: &lootitems::LootManager, // todo: remove invtree, appsaveitem, playerdata coupling
-        player_data: &mut PlayerData, brass_count_node: &Option<xot::Node>, stats_nodes:&Vec<xot::Node>, tool_level_ref: &Vec<savedata::ToolLevelRef>,
-        show_ui_state_error_msg: &mut String, saveinvref: &mut Vec<savedata::SaveInventoryItemRef>
+    pub fn save_tree_child_ui(ui: &mut egui::Ui, item: &mut savedata::SaveNodeTree, xtree: &mut xot::Xot, changes: &mut SaveChanges,
+        show_ui_state_error_msg: &mut String, view_state: &mut SaveTreeViewState, sibling_hint: &str
         )
     {
-        let id = ui.make_persistent_id(item.0);
+        let id = SaveTreeViewState::collapsing_id(item.0);
         egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
             .show_header(ui, |ui| {
-                ui.label(&item.1);
+                let b_selected = view_state.selected_node == Some(item.0);
+                let mut header_text = egui::RichText::new(format!("{}{}", item.1, sibling_hint));
+                if view_state.matched_nodes.contains(&item.0) {
+                    header_text = header_text.strong().color(ui.visuals().warn_fg_color);
+                };
+                let header_response = ui.selectable_label(b_selected, header_text)
+                    .on_hover_ui(|ui| {ui.label(savedata::SaveDataManager::get_node_path_xt(xtree, item.0));});
+                if let Some(key_predicate) = savedata::SaveDataManager::get_key_predicate_xt(xtree, item.0) {
+                    ui.weak(key_predicate);
+                };
+                if header_response.clicked() {
+                    view_state.selected_node = Some(item.0);
+                };
+                header_response.context_menu(|ui| {
+                    if ui.button("Copy path").clicked() {
+                        ui.ctx().copy_text(savedata::SaveDataManager::get_node_path_xt(xtree, item.0));
+                        ui.close_menu();
+                    };
+                    if ui.button("Copy query path").on_hover_text("Absolute path, inventory items picked by key").clicked() {
+                        ui.ctx().copy_text(savedata::SaveDataManager::get_node_query_path_xt(xtree, item.0));
+                        ui.close_menu();
+                    };
+                });
+                if b_selected && view_state.b_scroll_to_selected {
+                    header_response.scroll_to_me(Some(egui::Align::Center));
+                    view_state.b_scroll_to_selected = false;
+                };
             })
             .body(|body| {
-                if item.3 {
-                    if body.text_edit_singleline(&mut item.2).changed() {
-                        item.set_str_from_self(xtree);
-                        siir.iter_mut().for_each(|x| x.update_fromref_xt(xtree, lm)); // todo: remove invtree, appsaveitem, playerdata coupling
-                        Self::update_playerdata(player_data, xtree, brass_count_node, stats_nodes, tool_level_ref);
-                    };
+                item.load_children(xtree);
+                for (attribute_name, mut attribute_value) in savedata::SaveNodeTree::get_attributes(xtree, &item.0) {
+                    body.horizontal(|ui| {
+                        ui.label(format!("@{}", attribute_name));
+                        let mut attribute_result: Option<Result<(), String>> = None;
+                        if ui.text_edit_singleline(&mut attribute_value).changed() {
+                            attribute_result = Some(savedata::SaveNodeTree::set_attribute(xtree, &item.0, &attribute_name, &attribute_value));
+                        };
+                        if ui.small_button("x").on_hover_text("Remove attribute").clicked() {
+                            attribute_result = Some(savedata::SaveNodeTree::remove_attribute(xtree, &item.0, &attribute_name));
+                        };
+                        match attribute_result {
+                            Some(Ok(())) => changes.push(SaveChangeEvent::AttributeChanged(item.0)),
+                            Some(Err(s)) => *show_ui_state_error_msg = s,
+                            None => {},
+                        };
+                    });
+                };
+
+                if item.3 && Self::save_value_editor_ui(body, item, xtree, &mut view_state.value_types) {
+                    changes.push(SaveChangeEvent::TextChanged(item.0));
                 };
                 
+                // same named siblings get their position, e.g. item[3]
+                let mut name_counts: HashMap<String, usize> = HashMap::new();
+                item.4.iter().for_each(|x| *name_counts.entry(x.1.clone()).or_default() += 1);
+                let mut name_positions: HashMap<String, usize> = HashMap::new();
+
                 let child_row_num = item.4.len();
                 for child_idx in 0..child_row_num {
+                    let sibling_hint = match item.4.get(child_idx) {
+                        Some(child) if !child.1.is_empty() && name_counts.get(&child.1).is_some_and(|x| *x > 1) => {
+                            let position = name_positions.entry(child.1.clone()).or_default();
+                            *position += 1;
+                            format!("[{}]", position)
+                        },
+                        _ => String::new(),
+                    };
+                    if let (Some(visible_nodes), Some(child)) = (&view_state.visible_nodes, item.4.get(child_idx))
+                        && !visible_nodes.contains(&child.0)
+                    {
+                        continue;
+                    };
+
                     let mut child_node_deref: Option<xot::Node> = None;
                     if let Some(child) = item.4.get_mut(child_idx) {
                         child_node_deref = Some(child.0.clone());
 
-                        Self::save_tree_child_ui(body, child, xtree, siir, lm, player_data, brass_count_node, stats_nodes, tool_level_ref, 
-                            show_ui_state_error_msg, saveinvref
-                        );
+                        Self::save_tree_child_ui(body, child, xtree, changes, show_ui_state_error_msg, view_state, &sibling_hint);
                     };
 
-                    Self::tree_modify_ui(body, child_node_deref, item, xtree, 
-                        siir, lm, 
-                        player_data, brass_count_node, stats_nodes, tool_level_ref,
-                        show_ui_state_error_msg, saveinvref);
+                    Self::tree_modify_ui(body, child_node_deref, item, xtree, changes, show_ui_state_error_msg, view_state);
                 };
                 
             });
     }
 
+    /// Editor for a save tree value picked by its inferred type, returns true if the value was written.
+    pub fn save_value_editor_ui(ui: &mut egui::Ui, item: &mut savedata::SaveNodeTree, xtree: &mut xot::Xot, 
+        value_types: &mut savedata::SaveValueTypeCache) -> bool 
+    {
+        let value_type = value_types.get(xtree, item.0);
+        let mut b_changed = false;
+
+        ui.horizontal(|ui| {
+            match &value_type {
+                savedata::SaveValueType::Bool => {
+                    let mut b_value = item.2.trim().eq_ignore_ascii_case("true");
+                    if ui.checkbox(&mut b_value, "").changed() {
+                        item.2 = savedata::bool_text_like(b_value, &item.2).to_string();
+                        b_changed = true;
+                    };
+                },
+                savedata::SaveValueType::Int => {
+                    let mut value = item.2.trim().parse::<i64>().unwrap_or_default();
+                    if ui.add(egui::DragValue::new(&mut value)).changed() {
+                        item.2 = value.to_string();
+                        b_changed = true;
+                    };
+                },
+                savedata::SaveValueType::Number => {
+                    let mut value = item.2.trim().parse::<f64>().unwrap_or_default();
+                    if ui.add(egui::DragValue::new(&mut value)).changed() {
+                        item.2 = savedata::float_text_like(value, &item.2);
+                        b_changed = true;
+                    };
+                },
+                savedata::SaveValueType::Float => {
+                    let mut value = item.2.trim().parse::<f64>().unwrap_or_default();
+                    if ui.add(egui::DragValue::new(&mut value).speed(0.1)).changed() {
+                        item.2 = savedata::float_text_like(value, &item.2);
+                        b_changed = true;
+                    };
+                },
+                savedata::SaveValueType::Enum(options) => {
+                    let mut selected = item.2.trim().to_string();
+                    egui::ComboBox::from_id_salt(("save_value_enum", item.0))
+                        .selected_text(&selected)
+                        .show_ui(ui, |ui| {
+                            for option in options.iter() {
+                                ui.selectable_value(&mut selected, option.clone(), option);
+                            };
+                        });
+                    if selected != item.2.trim() {
+                        item.2 = selected;
+                        b_changed = true;
+                    };
+                },
+                savedata::SaveValueType::Guid | savedata::SaveValueType::Text => {
+                    let b_valid = value_type.validate(item.2.clone()).is_some();
+                    let mut text_edit = egui::TextEdit::singleline(&mut item.2);
+                    if !b_valid {text_edit = text_edit.text_color(ui.visuals().error_fg_color)};
+                    let response = ui.add(text_edit);
+                    // a guid is only valid once fully typed, write it when editing ends
+                    b_changed = match value_type {
+                        savedata::SaveValueType::Guid => response.lost_focus(),
+                        _ => response.changed(),
+                    };
+                },
+            };
+            ui.weak(value_type.label());
+        });
+
+        if b_changed {
+            item.set_str_from_self_with_check(xtree, |x| value_type.validate(x));
+        };
+        b_changed
+    }
+
     pub fn tree_modify_ui(ui: &mut egui::Ui, child_node_deref: Option<xot::Node>, item: &mut savedata::SaveNodeTree, xtree: &mut xot::Xot,
-        siir: &mut Vec<AppSaveInventoryItem>, lm: &lootitems::LootManager, // todo: remove invtree, appsaveitem, playerdata coupling
-        player_data: &mut PlayerData, brass_count_node: &Option<xot::Node>, stats_nodes:&Vec<xot::Node>, tool_level_ref: &Vec<savedata::ToolLevelRef,>,
-        show_ui_state_error_msg: &mut String,  saveinvref: &mut Vec<savedata::SaveInventoryItemRef>
+        changes: &mut SaveChanges, show_ui_state_error_msg: &mut String, view_state: &mut SaveTreeViewState
         ) 
     {
         ui.horizontal(|body| {
             body.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |body| {
                 if let Some(node_ref) = child_node_deref {
+                    body.menu_button("...", |ui| {
+                        let b_pinned = changes.is_pinned(node_ref);
+                        match Self::tree_structure_menu_ui(ui, node_ref, b_pinned, item, xtree, view_state) {
+                            None => {},
+                            Some(Ok(events)) => {
+                                events.into_iter().for_each(|x| changes.push(x));
+                                ui.close_menu();
+                            },
+                            Some(Err(s)) => *show_ui_state_error_msg = s,
+                        };
+                    });
                     if body.add_sized([20.0, 20.0], egui::Button::new("+")).clicked() {
                         match savedata::SaveNodeTree::copy_node(item, xtree, &node_ref) {
-                            Ok(new_node) => {
-                                let mut siir_idx_match: Option<usize> = None;
-                                let mut siir_item_match: Option<&AppSaveInventoryItem> = None;
-                                for (siir_idx, siir_item) in siir.iter().enumerate() {
-                                    if node_ref == siir_item.save_item_ref.item_node {
-                                        siir_idx_match = Some(siir_idx);
-                                        siir_item_match = Some(siir_item);
-                                        break
-                                    };
-                                };
-                                if let Some(siir_idx_match) = siir_idx_match {
-                                    match savedata::SaveDataManager::get_sir_from_item_node(new_node, xtree) {
-                                        Ok(si) => {
-                                            let asii = AppSaveInventoryItem::new_xt(&si, xtree, lm );
-                                            saveinvref.push(si);
-                                            siir.insert(siir_idx_match, asii);
-                                        },
-                                        Err(_e) => *show_ui_state_error_msg = "Could not add item to UI, please save and reload.".to_string(),
-                                    }
-                                };
-                            },
-                            Err(s) => {
-                                *show_ui_state_error_msg = s
-                            },
+                            Ok(new_node) => changes.push(SaveChangeEvent::NodeInserted { node: new_node, parent: item.0 }),
+                            Err(s) => *show_ui_state_error_msg = s,
                         };
                     };
                     if body.add_sized([20.0, 20.0], egui::Button::new("-")).clicked() {
-                        match savedata::SaveNodeTree::remove_node(item, xtree, &node_ref) {
-                            Ok(_) => {
-                                let mut siir_idx_match: Option<usize> = None;
-                                let mut siir_item_match: Option<&AppSaveInventoryItem> = None;
-                                for (siir_idx, siir_item) in siir.iter().enumerate() {
-                                    if node_ref == siir_item.save_item_ref.item_node {
-                                        siir_idx_match = Some(siir_idx);
-                                        siir_item_match = Some(siir_item);
-                                        break
-                                    };
-                                };
-                                if let (Some(siir_idx_match), Some(siir_item_match)) = (siir_idx_match, siir_item_match) {
-                                    let mut si_idx_match: Option<usize> = None;
-                                    for (si_idx, si) in saveinvref.iter().enumerate() {
-                                        if *si == siir_item_match.save_item_ref {
-                                            si_idx_match = Some(si_idx);
-                                        };
-                                    };
-                                    if let Some(si_idx_match) = si_idx_match {
-                                        saveinvref.remove(si_idx_match);
-                                    };
-                                    
-                                    siir.remove(siir_idx_match);
-                                }
-                            },
-                            Err(s) => *show_ui_state_error_msg = s,
+                        if changes.is_pinned_subtree(xtree, node_ref) {
+                            *show_ui_state_error_msg = "Can't remove, the editor uses this node or one below it.".to_string();
+                        } else {
+                            match savedata::SaveNodeTree::remove_node(item, xtree, &node_ref) {
+                                Ok(_) => changes.push(SaveChangeEvent::NodeRemoved { node: node_ref, parent: item.0 }),
+                                Err(s) => *show_ui_state_error_msg = s,
+                            };
                         };
                     };
                 };
@@ -396,29 +557,571 @@ impl App {
         });
     }
 
+    /// Structure and attribute actions for node, returns the changes made by the action taken this frame, if any.
+    /// Pinned nodes can't be unwrapped.
+    pub fn tree_structure_menu_ui(ui: &mut egui::Ui, node: xot::Node, b_pinned: bool, item: &mut savedata::SaveNodeTree, 
+        xtree: &mut xot::Xot, view_state: &mut SaveTreeViewState
+        ) -> Option<Result<Vec<SaveChangeEvent>, String>>
+    {
+        let b_element = xtree.is_element(node);
+        let parent_node = xtree.parent(node).unwrap_or(node);
+        let mut result: Option<Result<Vec<SaveChangeEvent>, String>> = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut view_state.edit_name);
+        });
+        let new_name = view_state.edit_name.trim().to_string();
+        let b_valid_name = savedata::SaveNodeTree::is_valid_element_name(&new_name);
+        ui.add_enabled_ui(b_valid_name, |ui| {
+            if ui.add_enabled(b_element, egui::Button::new("Rename")).clicked() {
+                result = Some(savedata::SaveNodeTree::rename_node(item, xtree, &node, &new_name)
+                    .map(|_| vec![SaveChangeEvent::NodeRenamed(node)]));
+            };
+            if ui.add_enabled(b_element, egui::Button::new("Insert child")).clicked() {
+                result = Some(savedata::SaveNodeTree::insert_new_child(item, xtree, &node, &new_name)
+                    .map(|x| vec![SaveChangeEvent::NodeInserted { node: x, parent: node }]));
+            };
+            if ui.button("Insert sibling after").clicked() {
+                result = Some(savedata::SaveNodeTree::insert_new_sibling(item, xtree, &node, &new_name)
+                    .map(|x| vec![SaveChangeEvent::NodeInserted { node: x, parent: parent_node }]));
+            };
+            if ui.button("Wrap in").clicked() {
+                result = Some(savedata::SaveNodeTree::wrap_node(item, xtree, &node, &new_name)
+                    .map(|x| vec![SaveChangeEvent::NodeInserted { node: x, parent: parent_node }]));
+            };
+        });
+        ui.separator();
+        if ui.button("Move up").clicked() {
+            result = Some(savedata::SaveNodeTree::move_node(item, xtree, &node, true).map(|_| vec![SaveChangeEvent::NodeMoved(node)]));
+        };
+        if ui.button("Move down").clicked() {
+            result = Some(savedata::SaveNodeTree::move_node(item, xtree, &node, false).map(|_| vec![SaveChangeEvent::NodeMoved(node)]));
+        };
+        if ui.add_enabled(b_element && !b_pinned, egui::Button::new("Unwrap")).clicked() {
+            let moved_nodes: Vec<xot::Node> = xtree.children(node).collect();
+            result = Some(savedata::SaveNodeTree::unwrap_node(item, xtree, &node).map(|_| {
+                std::iter::once(SaveChangeEvent::NodeRemoved { node, parent: parent_node })
+                    .chain(moved_nodes.into_iter().map(|x| SaveChangeEvent::NodeInserted { node: x, parent: parent_node }))
+                    .collect()
+            }));
+        };
+
+        if b_element {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Attribute");
+                ui.add(egui::TextEdit::singleline(&mut view_state.edit_attribute_name).hint_text("name").desired_width(80.0));
+                ui.add(egui::TextEdit::singleline(&mut view_state.edit_attribute_value).hint_text("value").desired_width(80.0));
+                if ui.button("Set").clicked() {
+                    result = Some(savedata::SaveNodeTree::set_attribute(xtree, &node, 
+                        view_state.edit_attribute_name.trim(), &view_state.edit_attribute_value)
+                        .map(|_| vec![SaveChangeEvent::AttributeChanged(node)]));
+                };
+            });
+        };
+
+        ui.separator();
+        if ui.button("Bookmark").on_hover_text("Add to Quick edits, labelled with Name if set").clicked() {
+            view_state.bookmark_request = Some(node);
+            ui.close_menu();
+        };
+        ui.horizontal(|ui| {
+            if ui.button("Copy as XML").clicked() {
+                match savedata::SaveNodeTree::node_to_xml(xtree, &node) {
+                    Ok(xml) => {
+                        ui.ctx().copy_text(xml);
+                        ui.close_menu();
+                    },
+                    Err(s) => result = Some(Err(s)),
+                };
+            };
+            if ui.button("Copy to file").clicked() {
+                result = match savedata::SaveNodeTree::node_to_xml(xtree, &node) {
+                    Ok(xml) => match std::fs::write(&view_state.xml_file_path, xml) {
+                        Ok(_) => Some(Ok(Vec::new())),
+                        Err(e) => Some(Err(format!("Could not write {}. {}", view_state.xml_file_path, e))),
+                    },
+                    Err(s) => Some(Err(s)),
+                };
+            };
+        });
+        ui.horizontal(|ui| {
+            ui.label("File");
+            ui.add(egui::TextEdit::singleline(&mut view_state.xml_file_path).hint_text("node.xml"));
+            if ui.button("Load").clicked() {
+                match std::fs::read_to_string(&view_state.xml_file_path) {
+                    Ok(xml) => view_state.paste_xml = xml,
+                    Err(e) => result = Some(Err(format!("Could not read {}. {}", view_state.xml_file_path, e))),
+                };
+            };
+        });
+        ui.add(egui::TextEdit::multiline(&mut view_state.paste_xml)
+            .hint_text("Paste XML here")
+            .code_editor()
+            .desired_rows(4));
+        ui.add_enabled_ui(!view_state.paste_xml.trim().is_empty(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.add_enabled(b_element, egui::Button::new("Paste as child")).clicked() {
+                    result = Some(savedata::SaveNodeTree::paste_xml(item, xtree, &node, &view_state.paste_xml, true)
+                        .map(|x| x.into_iter().map(|new_node| SaveChangeEvent::NodeInserted { node: new_node, parent: node }).collect()));
+                };
+                if ui.button("Paste as sibling after").clicked() {
+                    result = Some(savedata::SaveNodeTree::paste_xml(item, xtree, &node, &view_state.paste_xml, false)
+                        .map(|x| x.into_iter().map(|new_node| SaveChangeEvent::NodeInserted { node: new_node, parent: parent_node }).collect()));
+                };
+            });
+        });
+        result
+    }
+
     pub fn save_tree_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let mut b_query_set = false;
         egui::Window::new("Save Tree")
             .open(&mut self.show_ui_state.save_tree_window)
             .default_width(300.0)
             .vscroll(true)
             .show(ctx, |ui| {
+                let view_state = &mut self.show_ui_state.save_tree_view_state;
+                let mut b_run_search = false;
+                ui.horizontal(|ui| {
+                    ui.label("Search");
+                    let search_response = ui.text_edit_singleline(&mut view_state.search_query)
+                        .on_hover_text("Substring, /regex/, or numeric comparison e.g. >500, press Enter to search");
+                    let b_enter = search_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Search").clicked() || b_enter {b_run_search = true};
+                });
+                ui.horizontal(|ui| {
+                    b_run_search |= ui.checkbox(&mut view_state.search_scope.names, "Names").changed();
+                    b_run_search |= ui.checkbox(&mut view_state.search_scope.values, "Values").changed();
+                    b_run_search |= ui.checkbox(&mut view_state.search_scope.paths, "Paths").changed();
+                    b_run_search |= ui.checkbox(&mut view_state.b_filter_tree, "Filter tree").changed();
+                });
+
+                if b_run_search && let Some(tree) = &self.sm.save_tree {
+                    view_state.run_search(tree, &self.sm.xtree);
+                };
+
+                ui.horizontal(|ui| {
+                    ui.label("Query");
+                    let query_response = ui.text_edit_singleline(&mut view_state.query)
+                        .on_hover_text("Path query e.g. PlayerData/Inventory/AllItems/item[key/int=512]/value/InventoryItem/Count/int[3]");
+                    let b_enter = query_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Run").clicked() || b_enter {
+                        view_state.run_query(&self.sm);
+                    };
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Set matches to");
+                    ui.text_edit_singleline(&mut view_state.query_set_value);
+                    if ui.button("Set").clicked() {b_query_set = true};
+                });
+                if !view_state.query_error.is_empty() {
+                    ui.colored_label(egui::Color32::RED, &view_state.query_error);
+                };
+
+                if !view_state.search_results.is_empty() {
+                    ui.label(format!("{} results", view_state.search_results.len()));
+                    let mut clicked_node: Option<xot::Node> = None;
+                    egui::ScrollArea::vertical()
+                        .id_salt("save_tree_search_results")
+                        .max_height(150.0)
+                        .show(ui, |ui| {
+                            for result in view_state.search_results.iter() {
+                                let b_selected = view_state.selected_node == Some(result.node);
+                                let result_text = match result.text.is_empty() {
+                                    true => result.path.clone(),
+                                    false => format!("{} = {}", result.path, result.text),
+                                };
+                                if ui.selectable_label(b_selected, result_text).clicked() {
+                                    clicked_node = Some(result.node);
+                                };
+                            };
+                        });
+                    if let Some(node) = clicked_node {
+                        view_state.select_and_expand(ctx, &self.sm.xtree, node);
+                    };
+                };
+                ui.separator();
+
+                if let Some(selected_node) = view_state.selected_node 
+                    && !self.sm.xtree.is_removed(selected_node)
+                {
+                    let mut breadcrumb_node: Option<xot::Node> = None;
+                    ui.horizontal_wrapped(|ui| {
+                        let path_parts = savedata::SaveDataManager::get_node_path_parts_xt(&self.sm.xtree, selected_node);
+                        for (idx, (node, part)) in path_parts.iter().enumerate() {
+                            if idx > 0 {ui.weak("/");};
+                            if ui.link(part).clicked() {breadcrumb_node = Some(*node)};
+                        };
+                        if ui.small_button("Copy path").clicked() {
+                            ctx.copy_text(savedata::SaveDataManager::get_node_path_xt(&self.sm.xtree, selected_node));
+                        };
+                    });
+                    if let Some(node) = breadcrumb_node {
+                        view_state.select_and_expand(ctx, &self.sm.xtree, node);
+                    };
+                    ui.separator();
+                };
+
                 if let Some(item) = &mut self.sm.save_tree {
-                    Self::save_tree_child_ui(ui, item, &mut self.sm.xtree, 
-                        &mut self.save_inventory_items,  &self.lm, 
-                        &mut self.player_data, &self.sm.brass_count_node, &self.sm.stats_nodes, &self.sm.tool_level_ref, 
-                        &mut self.show_ui_state.error_msg, &mut self.sm.save_inventory_ref
-                    ); // todo: remove invtree, appsaveitem, playerdata coupling
+                    Self::save_tree_child_ui(ui, item, &mut self.sm.xtree, &mut self.sm.changes, 
+                        &mut self.show_ui_state.error_msg, view_state, ""
+                    );
+                };
+            });
+
+        if let Some(node) = self.show_ui_state.save_tree_view_state.bookmark_request.take() {
+            let label = match self.show_ui_state.save_tree_view_state.edit_name.trim() {
+                "" => savedata::SaveDataManager::get_name_from_node_xt(&self.sm.xtree, node).unwrap_or_default().to_string(),
+                x => x.to_string(),
+            };
+            self.add_bookmark(node, label);
+        };
+
+        if b_query_set {
+            let view_state = &mut self.show_ui_state.save_tree_view_state;
+            match self.sm.query_set_str(&view_state.query, &view_state.query_set_value) {
+                Ok(set_count) => self.show_ui_state.error_msg = format!("Set {} nodes.", set_count),
+                Err(e) => self.show_ui_state.save_tree_view_state.query_error = e.to_string(),
+            };
+            self.show_ui_state.save_tree_view_state.run_query(&self.sm);
+        };
+    }
+
+    pub fn add_bookmark(&mut self, node: xot::Node, label: String) {
+        let path = savedata::SaveDataManager::get_node_query_path_xt(&self.sm.xtree, node);
+        self.show_ui_state.error_msg = format!("Bookmarked {}", path);
+        self.appconfig.bookmarks.push(config::SaveBookmark { label, path });
+        self.sm.resolve_bookmarks(&self.appconfig.bookmarks);
+        if let Err(e) = config::store_appconfig(&self.appconfig) {
+            self.show_ui_state.error_msg = format!("Could not save bookmarks to config. {}", e);
+        };
+        self.show_ui_state.quick_edits_window = true;
+    }
+
+    pub fn quick_edits_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let mut remove_idx: Option<usize> = None;
+        let mut show_node: Option<xot::Node> = None;
+        let mut b_store_config = false;
+        egui::Window::new("Quick edits")
+            .open(&mut self.show_ui_state.quick_edits_window)
+            .default_width(300.0)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if self.appconfig.bookmarks.is_empty() {
+                    ui.label("No bookmarks, add them from the \"...\" menu of a node in the Save tree.");
+                };
+                egui::Grid::new("quick_edits").striped(true).show(ui, |ui| {
+                    for (idx, bookmark) in self.appconfig.bookmarks.iter_mut().enumerate() {
+                        let label_response = ui.add(egui::TextEdit::singleline(&mut bookmark.label).desired_width(120.0))
+                            .on_hover_text(&bookmark.path);
+                        if label_response.lost_focus() {b_store_config = true};
+
+                        let nodes = self.sm.bookmark_nodes.get(idx).cloned().unwrap_or_default();
+                        ui.vertical(|ui| {
+                            if nodes.is_empty() {ui.weak("Not found in this save");};
+                            for node in nodes.iter() {
+                                let mut tree_item = savedata::SaveNodeTree::new(node, &self.sm.xtree);
+                                let b_leaf = !self.sm.xtree.children(*node).any(|x| self.sm.xtree.is_element(x));
+                                ui.push_id(node, |ui| {
+                                    if tree_item.3 && b_leaf {
+                                        if Self::save_value_editor_ui(ui, &mut tree_item, &mut self.sm.xtree, 
+                                            &mut self.show_ui_state.save_tree_view_state.value_types) 
+                                        {
+                                            self.sm.changes.push(SaveChangeEvent::TextChanged(*node));
+                                        };
+                                    } else {
+                                        ui.weak(format!("{} (no value)", tree_item.1));
+                                    };
+                                });
+                            };
+                        });
+
+                        ui.horizontal(|ui| {
+                            if let Some(node) = nodes.first() && ui.small_button("Show").on_hover_text("Show in Save tree").clicked() {
+                                show_node = Some(*node);
+                            };
+                            if ui.small_button("x").on_hover_text("Remove bookmark").clicked() {
+                                remove_idx = Some(idx);
+                            };
+                        });
+                        ui.end_row();
+                    };
+                });
+            });
+
+        if let Some(idx) = remove_idx {
+            self.appconfig.bookmarks.remove(idx);
+            self.sm.resolve_bookmarks(&self.appconfig.bookmarks);
+            b_store_config = true;
+        };
+        if b_store_config && let Err(e) = config::store_appconfig(&self.appconfig) {
+            self.show_ui_state.error_msg = format!("Could not save bookmarks to config. {}", e);
+        };
+        if let Some(node) = show_node {
+            self.show_ui_state.save_tree_window = true;
+            self.show_ui_state.save_tree_view_state.select_and_expand(ctx, &self.sm.xtree, node);
+        };
+    }
+
+    /// The top level parts of the save grouped by what they're probably about, each with editors picked by its shape.
+    pub fn save_overview_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.show_ui_state.save_survey.is_empty() {self.show_ui_state.save_survey.refresh(&self.sm)};
+        let mut show_node: Option<xot::Node> = None;
+        egui::Window::new("Save overview")
+            .open(&mut self.show_ui_state.save_overview_window)
+            .default_width(400.0)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Show");
+                    let category_filter = &mut self.show_ui_state.save_overview_category;
+                    egui::ComboBox::from_id_salt("save_overview_category")
+                        .selected_text(category_filter.map_or("All", |x| x.label()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(category_filter, None, "All");
+                            for category in savesurvey::SurveyCategory::iter() {
+                                ui.selectable_value(category_filter, Some(category), category.label());
+                            };
+                        });
+                    ui.checkbox(&mut self.show_ui_state.b_save_overview_hide_edited, "Hide parts with their own editor");
+                });
+                ui.separator();
+
+                for category in savesurvey::SurveyCategory::iter() {
+                    if self.show_ui_state.save_overview_category.is_some_and(|x| x != category) {continue};
+                    let entries: Vec<savesurvey::SurveyEntry> = self.show_ui_state.save_survey.entries_in(category)
+                        .filter(|x| !(self.show_ui_state.b_save_overview_hide_edited && x.b_has_editor))
+                        .cloned()
+                        .collect();
+                    if entries.is_empty() {continue};
+                    ui.strong(category.label());
+                    for entry in entries.iter() {
+                        ui.push_id(entry.node, |ui| {
+                            Self::save_overview_entry_ui(ui, entry, &mut self.sm, &mut self.show_ui_state.save_tree_view_state.value_types, &mut show_node);
+                        });
+                    };
+                };
+            });
+
+        if let Some(node) = show_node {
+            self.show_ui_state.save_tree_window = true;
+            self.show_ui_state.save_tree_view_state.select_and_expand(ctx, &self.sm.xtree, node);
+        };
+    }
+
+    fn save_overview_entry_ui(ui: &mut egui::Ui, entry: &savesurvey::SurveyEntry, sm: &mut savedata::SaveDataManager, 
+        value_types: &mut savedata::SaveValueTypeCache, show_node: &mut Option<xot::Node>) 
+    {
+        if let savesurvey::NodeShape::Value(_) | savesurvey::NodeShape::Empty = entry.shape {
+            ui.horizontal(|ui| {
+                ui.label(&entry.name).on_hover_text(&entry.path);
+                Self::save_overview_value_ui(ui, entry.node, sm, value_types);
+                if ui.small_button("Show").on_hover_text("Show in Save tree").clicked() {*show_node = Some(entry.node)};
+            });
+            return;
+        };
+
+        egui::CollapsingHeader::new(format!("{} ({})", entry.name, entry.shape.summary()))
+            .id_salt(("save_overview", entry.node))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.weak(&entry.path);
+                    if ui.small_button("Show").on_hover_text("Show in Save tree").clicked() {*show_node = Some(entry.node)};
+                });
+                if entry.b_has_editor {ui.weak("Also has an editor of its own, see the Player data window, inventory table or top panel.");};
+                Self::save_overview_fields_ui(ui, entry.node, sm, value_types, show_node);
+            });
+    }
+
+    /// A row per child of node: values get an editor, lists and records open up in turn.
+    fn save_overview_fields_ui(ui: &mut egui::Ui, node: xot::Node, sm: &mut savedata::SaveDataManager, 
+        value_types: &mut savedata::SaveValueTypeCache, show_node: &mut Option<xot::Node>) 
+    {
+        const MAX_ROWS: usize = 200;
+        let children: Vec<xot::Node> = sm.xtree.children(node).filter(|x| sm.xtree.is_element(*x)).collect();
+        let b_list = matches!(savesurvey::NodeShape::of_node(&sm.xtree, node), savesurvey::NodeShape::List { .. });
+
+        for (idx, child) in children.iter().enumerate().take(MAX_ROWS) {
+            let name = sm.get_name_from_node(*child).unwrap_or_default().to_string();
+            let label = match savedata::SaveDataManager::get_key_predicate_xt(&sm.xtree, *child) {
+                Some(predicate) => format!("{}[{}]", name, predicate),
+                None if b_list => format!("{}[{}]", name, idx + 1),
+                None => name,
+            };
+
+            ui.push_id(child, |ui| {
+                // leaves are values (or empty), checked first so their type isn't inferred here every frame
+                if !sm.xtree.children(*child).any(|x| sm.xtree.is_element(x)) {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        Self::save_overview_value_ui(ui, *child, sm, value_types);
+                    });
+                    return;
                 };
+                let shape = savesurvey::NodeShape::of_node(&sm.xtree, *child);
+                egui::CollapsingHeader::new(format!("{} ({})", label, shape.summary()))
+                    .id_salt(("save_overview", *child))
+                    .show(ui, |ui| {
+                        if ui.small_button("Show").on_hover_text("Show in Save tree").clicked() {*show_node = Some(*child)};
+                        Self::save_overview_fields_ui(ui, *child, sm, value_types, show_node);
+                    });
             });
+        };
+        if children.len() > MAX_ROWS {
+            ui.weak(format!("{} more, see the Save tree.", children.len() - MAX_ROWS));
+        };
+    }
+
+    fn save_overview_value_ui(ui: &mut egui::Ui, node: xot::Node, sm: &mut savedata::SaveDataManager, value_types: &mut savedata::SaveValueTypeCache) {
+        let mut tree_item = savedata::SaveNodeTree::new(&node, &sm.xtree);
+        if !tree_item.3 {
+            ui.weak("(no value)");
+            return;
+        };
+        if Self::save_value_editor_ui(ui, &mut tree_item, &mut sm.xtree, value_types) {
+            sm.changes.push(SaveChangeEvent::TextChanged(node));
+        };
     }
 
     pub fn loot_ref_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         use egui_extras::{Column, TableBuilder};
         egui::Window::new("Loot reference")
             .open(&mut self.show_ui_state.loot_ref_window)
+            .default_width(600.0)
+            .vscroll(true)
+            .hscroll(true)
+            .show(ctx, |ui| {
+                if self.show_ui_state.loot_ref_view.rows.is_empty() {
+                    self.show_ui_state.loot_ref_view.rows = self.lm.full_item_lookup.values()
+                        .map(|x| LootRefRow::new(x, &self.lm, &self.arm))
+                        .collect();
+                };
+
+                if let Some(selected_uid) = self.show_ui_state.loot_ref_selected_uid
+                    && let Some(row) = self.show_ui_state.loot_ref_view.rows.iter().find(|x| x.item.uid == selected_uid)
+                {
+                    ui.horizontal(|ui| {
+                        ui.strong(format!("{} ({})", row.item.name, row.item.uid));
+                        if ui.button("Close").clicked() {self.show_ui_state.loot_ref_selected_uid = None};
+                    });
+                    egui::Grid::new("loot_ref_detail").striped(true).show(ui, |ui| {
+                        for col in LootRefColumn::iter() {
+                            ui.label(col.label());
+                            ui.label(row.cell_text(col));
+                            ui.end_row();
+                        };
+                        ui.label("Star rating source");
+                        ui.label(row.star_rating_source.to_string());
+                        ui.end_row();
+                        for recipe in self.arm.get_recipes_for_item(selected_uid) {
+                            ui.label("Apothecary");
+                            ui.label(format!("{} ({:?})", self.arm.get_ailment_name(recipe.ailment_id), recipe.result_type));
+                            ui.end_row();
+                        };
+                    });
+                    ui.separator();
+                };
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Name");
+                    ui.add(egui::TextEdit::singleline(&mut self.show_ui_state.loot_ref_name_filter).desired_width(100.0));
+                    ui.label("Type");
+                    ui.add(egui::TextEdit::singleline(&mut self.show_ui_state.loot_ref_type_filter).desired_width(100.0));
+                    tristate_filter_ui(ui, "Has stars", &mut self.show_ui_state.loot_ref_star_rating_filter);
+                    tristate_filter_ui(ui, "Liquid", &mut self.show_ui_state.loot_ref_liquid_filter);
+                    tristate_filter_ui(ui, "Hide qty", &mut self.show_ui_state.loot_ref_hide_quantity_filter);
+                    tristate_filter_ui(ui, "Carryable", &mut self.show_ui_state.loot_ref_carryable_filter);
+                    egui::ComboBox::from_label("Cure")
+                        .selected_text(match self.show_ui_state.loot_ref_cure_filter {
+                            Some(x) => format!("{:?}", x),
+                            None => "Any".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.show_ui_state.loot_ref_cure_filter, None, "Any");
+                            for cure_status in [apothrecipes::CureStatus::FullCure, apothrecipes::CureStatus::PartialCure,
+                                apothrecipes::CureStatus::FailureOrPlacebo, apothrecipes::CureStatus::SideEffect] 
+                            {
+                                ui.selectable_value(&mut self.show_ui_state.loot_ref_cure_filter, Some(cure_status), format!("{:?}", cure_status));
+                            };
+                        });
+                });
+
+                let filter_key: LootRefFilterKey = (self.show_ui_state.loot_ref_name_filter.to_lowercase(), self.show_ui_state.loot_ref_type_filter.to_lowercase(),
+                    [self.show_ui_state.loot_ref_star_rating_filter, self.show_ui_state.loot_ref_liquid_filter, 
+                        self.show_ui_state.loot_ref_hide_quantity_filter, self.show_ui_state.loot_ref_carryable_filter],
+                    self.show_ui_state.loot_ref_cure_filter, self.show_ui_state.loot_ref_sort_column, self.show_ui_state.loot_ref_sort_descending);
+                if self.show_ui_state.loot_ref_view.shown_key.as_ref() != Some(&filter_key) {
+                    self.show_ui_state.loot_ref_view.update_shown(filter_key);
+                };
+                let view = &self.show_ui_state.loot_ref_view;
+
+                let table = TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(false)
+                    .cell_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown))
+                    .columns(Column::auto().at_least(40.0).resizable(true), LootRefColumn::iter().count())
+                    .min_scrolled_height(0.0);
+
+                table
+                    .header(20.0, |mut header| {
+                        for col in LootRefColumn::iter() {
+                            header.col(|ui| {
+                                let mut label = col.label().to_string();
+                                if col == self.show_ui_state.loot_ref_sort_column {
+                                    label.push_str(if self.show_ui_state.loot_ref_sort_descending {" v"} else {" ^"});
+                                };
+                                if ui.add(egui::Button::new(egui::RichText::new(label).strong()).frame(false)).clicked() {
+                                    if col == self.show_ui_state.loot_ref_sort_column {
+                                        self.show_ui_state.loot_ref_sort_descending = !self.show_ui_state.loot_ref_sort_descending;
+                                    } else {
+                                        self.show_ui_state.loot_ref_sort_column = col;
+                                        self.show_ui_state.loot_ref_sort_descending = false;
+                                    };
+                                };
+                            });
+                        };
+                    })
+                    .body(|body| {
+                        let row_height = 30.0;
+                        let num_rows = view.shown.len();
+                        body.rows(row_height, num_rows, |mut row| {
+                            let loot_row = &view.rows[view.shown[row.index()]];
+                            for col in LootRefColumn::iter() {
+                                row.col(|ui| {
+                                    match col {
+                                        LootRefColumn::Uid => {
+                                            let b_selected = self.show_ui_state.loot_ref_selected_uid == Some(loot_row.item.uid);
+                                            if ui.selectable_label(b_selected, loot_row.cell_text(col)).clicked() {
+                                                self.show_ui_state.loot_ref_selected_uid = Some(loot_row.item.uid);
+                                            };
+                                        },
+                                        LootRefColumn::CureStatus => {
+                                            if let Some(cure_status) = loot_row.cure_status {
+                                                cure_status_badge_ui(ui, cure_status);
+                                            };
+                                        },
+                                        _ => {ui.label(loot_row.cell_text(col));},
+                                    };
+                                });
+                            };
+                        })
+                    });
+                });
+    }
+
+    pub fn star_rating_report_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        use egui_extras::{Column, TableBuilder};
+        egui::Window::new("Star rating report")
+            .open(&mut self.show_ui_state.star_rating_report_window)
             .default_width(300.0)
             .vscroll(true)
             .show(ctx, |ui| {
+                let default_uids = self.lm.get_default_star_rating_uids();
+                ui.label(format!("{} items have star ratings only because no condition in {} matched.", 
+                    default_uids.len(), self.appconfig.filename_saveedit_has_star_rating_conditions));
+
                 let table = TableBuilder::new(ui)
                     .striped(true)
                     .resizable(false)
@@ -426,7 +1129,6 @@ impl App {
                     .column(Column::initial(40.0).at_least(40.0))
                     .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
                     .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
-                    .column(Column::initial(40.0).at_least(40.0))
                     .min_scrolled_height(0.0);
 
                 table
@@ -435,42 +1137,97 @@ impl App {
                             ui.strong("uid");
                         });
                         header.col(|ui| {
-                            ui.text_edit_singleline(&mut self.show_ui_state.loot_ref_name_filter);
+                            ui.strong("Name");
                         });
                         header.col(|ui| {
-                            ui.text_edit_singleline(&mut self.show_ui_state.loot_ref_type_filter);
+                            ui.strong("Type");
+                        });
+                    })
+                    .body(|body| {
+                        let row_height = 30.0;
+                        let num_rows = default_uids.len();
+                        body.rows(row_height, num_rows, |mut row| {
+                            let li = &self.lm.full_item_lookup[&default_uids[row.index()]];
+                            row.col(|ui| {
+                                ui.label(li.uid.to_string());
+                            });
+                            row.col(|ui| {
+                                ui.label(&li.name);
+                            });
+                            row.col(|ui| {
+                                ui.label(self.lm.pickup_type_lookup_rev.get(&li.type_of_pickup).cloned().unwrap_or_default());
+                            });
+                        })
+                    });
+            });
+    }
+
+    pub fn apoth_ref_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        use egui_extras::{Column, TableBuilder};
+        egui::Window::new("Apothecary reference")
+            .open(&mut self.show_ui_state.apoth_ref_window)
+            .default_width(300.0)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if self.arm.ailment_lookup.is_empty() {
+                    ui.label(format!("No ailment names are shipped, ailments show their ids. Add Name|AilmentID rows to {} to name them.",
+                        PathBuf::from_iter([&self.appconfig.path_saveedit_data, &self.appconfig.filename_saveedit_ailment_lookup]).display()));
+                };
+                let table = TableBuilder::new(ui)
+                    .striped(true)
+                    .resizable(false)
+                    .cell_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown))
+                    .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
+                    .column(Column::initial(100.0).at_least(40.0))
+                    .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
+                    .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
+                    .min_scrolled_height(0.0);
+
+                let item_name = |uid: i32| -> String {
+                    match self.lm.full_item_lookup.get(&uid) {
+                        Some(li) => format!("{} ({})", li.name, uid),
+                        None => if uid < 0 {"".to_string()} else {uid.to_string()},
+                    }
+                };
+
+                table
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.text_edit_singleline(&mut self.show_ui_state.apoth_ref_ailment_filter);
+                        });
+                        header.col(|ui| {
+                            ui.strong("Result");
                         });
                         header.col(|ui| {
-                            ui.strong("Cost");
+                            ui.strong("Item");
+                        });
+                        header.col(|ui| {
+                            ui.strong("With side effects");
                         });
                     })
                     .body(|body| {
-                        let mut filtered_lm: Vec<_> = self.lm.full_item_lookup.values().cloned().collect();
-                        filtered_lm.retain(|v|  {
-                            self.show_ui_state.loot_ref_name_filter.is_empty() || 
-                            v.name.to_lowercase().contains(&self.show_ui_state.loot_ref_name_filter.to_lowercase())
-                        } );
-                        filtered_lm.retain(|v|  {
-                            self.show_ui_state.loot_ref_type_filter.is_empty() || 
-                            self.lm.pickup_type_lookup_rev[&v.type_of_pickup].to_lowercase().contains(&self.show_ui_state.loot_ref_type_filter.to_lowercase())
-                        } );
-                        filtered_lm.sort_by_key(|x| x.uid);
+                        let mut filtered_cures: Vec<&apothrecipes::ApothRecipe> = self.arm.all_cures.iter().collect();
+                        filtered_cures.retain(|v| {
+                            self.show_ui_state.apoth_ref_ailment_filter.is_empty() ||
+                            self.arm.get_ailment_name(v.ailment_id).to_lowercase().contains(&self.show_ui_state.apoth_ref_ailment_filter.to_lowercase())
+                        });
+                        filtered_cures.sort_by_key(|x| (x.ailment_id, x.result_type.clone() as i32));
 
                         let row_height = 30.0;
-                        let num_rows = filtered_lm.len();
+                        let num_rows = filtered_cures.len();
                         body.rows(row_height, num_rows, |mut row| {
-                            let row_index = row.index();
+                            let cure = filtered_cures[row.index()];
                             row.col(|ui| {
-                                ui.label(format!("{}", filtered_lm[row_index].uid));
+                                ui.label(self.arm.get_ailment_name(cure.ailment_id));
                             });
                             row.col(|ui| {
-                                ui.label(&filtered_lm[row_index].name);
+                                ui.label(format!("{:?}", cure.result_type));
                             });
                             row.col(|ui| {
-                                ui.label(&self.lm.pickup_type_lookup_rev[&filtered_lm[row_index].type_of_pickup]);
+                                ui.label(item_name(cure.item_id));
                             });
                             row.col(|ui| {
-                                ui.label(format!("{}", filtered_lm[row_index].cost));
+                                ui.label(item_name(cure.item_id_with_side_effects));
                             });
                         })
                     });
@@ -478,90 +1235,17 @@ impl App {
     }
 
     pub fn player_data_window(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) { // todo: change into tables
+        let sc = savesections::SectionContext { appconfig: &self.appconfig, lm: &self.lm, arm: &self.arm, rd: &self.rd };
         egui::Window::new("Player Data")
             .open(&mut self.show_ui_state.player_data_window)
             .default_width(300.0)
             .vscroll(true)
             .show(ctx, |ui| {
                 ui.vertical(|ui| {
-                    ui.horizontal(|contents| {
-                        contents.columns(2, |columns| {
-                            columns[0].add(egui::Label::new("Brass"));
-                            let brass_response = columns[1].add(egui::DragValue::new(&mut self.player_data.brass));
-                            if brass_response.changed() && self.sm.brass_count_node.is_some() {
-                                match self.sm.xtree.text_content_mut(self.sm.brass_count_node.unwrap()) {
-                                    Some(brass_mut_text) => {
-                                        brass_mut_text.set(format!("{}", self.player_data.brass));
-                                        if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                            x.update_all_strings(&self.sm.xtree);
-                                        };
-                                    },
-                                    None => {},
-                                }
-                            };
-                        });
-                    });
-
-                    ui.add(egui::Label::new("Player stats"));
-                    for item in self.player_data.stats.iter_mut() {
-                        ui.horizontal(|contents| {
-                            contents.columns(2, |columns| {
-                                columns[0].add(egui::Label::new(item.1.clone()));
-                                let stats_response = columns[1].add(egui::DragValue::new(&mut item.2));
-                                if stats_response.changed() {
-                                    match self.sm.xtree.text_content_mut(self.sm.stats_nodes[item.0]) {
-                                        Some(stat_mut_text) => {
-                                            stat_mut_text.set(format!("{}", item.2));
-                                            if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                                x.update_all_strings(&self.sm.xtree);
-                                            };
-                                        },
-                                        None => {},
-                                    }
-                                };
-                            });
-                        });
+                    for (idx, section) in self.sections.iter_placed_mut(savesections::SectionPlacement::PlayerDataWindow).enumerate() {
+                        if idx > 0 {ui.separator();};
+                        section.ui(ui, &mut self.sm, &sc, &mut self.show_ui_state.error_msg);
                     };
-
-                    ui.horizontal(|contents| {
-                        contents.columns(3, |columns| {
-                            columns[0].label("Tool");
-                            columns[1].label("Level");
-                            columns[2].label("XP");
-                        });
-                    });
-                    for item in self.player_data.tool_level.iter_mut() {
-                        ui.horizontal(|contents| {
-                            contents.columns(3, |columns| {
-                                columns[0].add(egui::Label::new(item.1.clone()));
-                                let tool_level_response = columns[1].add(egui::DragValue::new(&mut item.2));
-                                if tool_level_response.changed() {
-                                    match self.sm.xtree.text_content_mut(self.sm.tool_level_ref[item.0].tool_level_node) {
-                                        Some(tool_level_mut_text) => {
-                                            tool_level_mut_text.set(format!("{}", item.2));
-                                            if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                                x.update_all_strings(&self.sm.xtree);
-                                            };
-                                    },
-                                        None => {},
-                                    }
-                                };
-                                let tool_xp_response = columns[2].add(egui::DragValue::new(&mut item.3));
-                                if tool_xp_response.changed() {
-                                    match self.sm.xtree.text_content_mut(self.sm.tool_level_ref[item.0].tool_current_xp_node) {
-                                        Some(tool_xp_mut_text) => {
-                                            tool_xp_mut_text.set(format!("{:.1}", item.3));
-                                            if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                                x.update_all_strings(&self.sm.xtree);
-                                            };
-                                        },
-                                        None => {},
-                                    }
-                                };
-                            });
-                        });
-                    };
-
                 });
             });
     }
@@ -573,21 +1257,88 @@ impl App {
             .vscroll(true)
             .show(ctx, |ui| {
                 ui.horizontal(|contents| {
-                    if contents.button("Save").clicked() {
-                        let config_filepath = config::get_config_filepath();
-                        confy::store_path(config_filepath.as_path(), self.appconfig.clone()).unwrap();
+                    if contents.button("Save").clicked() && let Err(e) = config::store_appconfig(&self.appconfig) {
+                        self.show_ui_state.error_msg = format!("Could not save config. {}", e);
                     };
                     if contents.button("Reload").clicked() {
                         Self::reload_data(&mut self.appconfig, &mut self.lm, &mut self.sm, 
-                            &mut self.save_inventory_items, &mut self.arm, 
-                            &mut self.show_ui_state.error_during_load, &mut self.show_ui_state.error_msg, 
-                            &mut self.player_data)
-
+                            &mut self.sections, &mut self.arm, &mut self.rd, 
+                            &mut self.show_ui_state.error_during_load, &mut self.show_ui_state.error_msg);
+                        self.show_ui_state.save_survey.clear();
+                        self.show_ui_state.loot_ref_view.clear();
+                        self.show_ui_state.save_tree_view_state.value_types.clear();
                     };
                     if contents.button("Reset to default").clicked() {
-                        self.appconfig = config::AppConfig::default();
+                        self.appconfig = config::AppConfig { profile_name: self.appconfig.profile_name.clone(), ..Default::default() };
+                    };
+                });
+
+                ui.separator();
+
+                let mut switch_to: Option<String> = None;
+                ui.horizontal(|contents| {
+                    contents.label("Profile");
+                    egui::ComboBox::from_id_salt("options_profile")
+                        .selected_text(&self.appconfig.profile_name)
+                        .show_ui(contents, |ui| {
+                            for profile_name in self.show_ui_state.profile_names.iter() {
+                                if ui.selectable_label(*profile_name == self.appconfig.profile_name, profile_name).clicked() {
+                                    switch_to = Some(profile_name.clone());
+                                };
+                            };
+                        })
+                        .response.on_hover_text("Switching reloads the editor with that profile's settings, unsaved option changes are lost");
+                    let b_named_profile = self.appconfig.profile_name != config::DEFAULT_PROFILE_NAME;
+                    if contents.add_enabled(b_named_profile, egui::Button::new("Delete profile")).clicked() {
+                        match config::load_config_file() {
+                            Ok(mut config_file) => {
+                                config_file.remove_profile(&self.appconfig.profile_name);
+                                match config::store_config_file(&config_file) {
+                                    Ok(_) => switch_to = Some(config::DEFAULT_PROFILE_NAME.to_string()),
+                                    Err(e) => self.show_ui_state.error_msg = format!("Could not save config. {}", e),
+                                };
+                            },
+                            Err(e) => self.show_ui_state.error_msg = e.to_string(),
+                        };
+                    };
+                });
+                ui.horizontal(|contents| {
+                    contents.add(egui::TextEdit::singleline(&mut self.show_ui_state.new_profile_name).hint_text("new profile name"));
+                    if contents.button("Save as new profile").on_hover_text("Saves these options under the new name and switches to it").clicked() {
+                        let new_profile_name = self.show_ui_state.new_profile_name.trim().to_string();
+                        if new_profile_name.is_empty() || self.show_ui_state.profile_names.contains(&new_profile_name) {
+                            self.show_ui_state.error_msg = format!("Profile name '{}' is empty or already used.", new_profile_name);
+                        } else {
+                            let appconfig = config::AppConfig { profile_name: new_profile_name.clone(), ..self.appconfig.clone() };
+                            match config::store_appconfig(&appconfig) {
+                                Ok(_) => {
+                                    switch_to = Some(new_profile_name);
+                                    self.show_ui_state.new_profile_name.clear();
+                                },
+                                Err(e) => self.show_ui_state.error_msg = format!("Could not save config. {}", e),
+                            };
+                        };
                     };
                 });
+                if let Some(profile_name) = switch_to {
+                    match config::load_config_file() {
+                        Ok(mut config_file) => {
+                            config_file.active_profile = profile_name.clone();
+                            if let Err(e) = config::store_config_file(&config_file) {
+                                self.show_ui_state.error_msg = format!("Could not save config. {}", e);
+                            };
+                            self.show_ui_state.profile_names = config_file.profile_names();
+                        },
+                        Err(e) => self.show_ui_state.error_msg = e.to_string(),
+                    };
+                    self.appconfig.profile_name = profile_name;
+                    Self::reload_data(&mut self.appconfig, &mut self.lm, &mut self.sm,
+                        &mut self.sections, &mut self.arm, &mut self.rd,
+                        &mut self.show_ui_state.error_during_load, &mut self.show_ui_state.error_msg);
+                    self.show_ui_state.save_survey.clear();
+                    self.show_ui_state.loot_ref_view.clear();
+                    self.show_ui_state.save_tree_view_state.value_types.clear();
+                };
 
                 ui.separator();
 
@@ -625,12 +1376,23 @@ impl App {
                     contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_kynseed_apothrecipes).desired_width(f32::INFINITY));
                 });
 
+                ui.vertical(|contents| {
+                    contents.add(egui::Label::new("cure_quality_guard"));
+                    egui::ComboBox::from_id_salt("cure_quality_guard")
+                        .selected_text(format!("{:?}", self.appconfig.cure_quality_guard))
+                        .show_ui(contents, |ui| {
+                            for guard in [config::CureQualityGuard::Off, config::CureQualityGuard::Warn, config::CureQualityGuard::Clamp] {
+                                ui.selectable_value(&mut self.appconfig.cure_quality_guard, guard, format!("{:?}", guard));
+                            };
+                        });
+                });
+
                 ui.separator();
                 
                 ui.checkbox(&mut self.appconfig.b_use_embedded_saveedit_data, "Use embedded saveedit data");
                 ui.vertical(|contents| {
                     contents.add(egui::Label::new("path_saveedit_data"));
-                    contents.add_enabled(!self.appconfig.b_use_embedded_saveedit_data, egui::TextEdit::singleline(&mut self.appconfig.path_saveedit_data).desired_width(f32::INFINITY));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.path_saveedit_data).desired_width(f32::INFINITY));
                 });
                 ui.vertical(|contents| {
                     contents.add(egui::Label::new("filename_saveedit_has_star_rating_conditions"));
@@ -652,6 +1414,24 @@ impl App {
                     contents.add(egui::Label::new("filename_saveedit_pickup_types"));
                     contents.add_enabled(!self.appconfig.b_use_embedded_saveedit_data, egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_pickup_types).desired_width(f32::INFINITY));
                 });
+                ui.vertical(|contents| {
+                    contents.add(egui::Label::new("filename_saveedit_ailment_lookup"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_ailment_lookup).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_tool_levels"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_tool_levels).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_stats_info"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_stats_info).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_calendar"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_calendar).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_npc_lookup"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_npc_lookup).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_relationship_fields"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_relationship_fields).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_farm"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_farm).desired_width(f32::INFINITY));
+                    contents.add(egui::Label::new("filename_saveedit_known_recipes"));
+                    contents.add(egui::TextEdit::singleline(&mut self.appconfig.filename_saveedit_known_recipes).desired_width(f32::INFINITY));
+                });
             });
     }
 
@@ -668,10 +1448,16 @@ impl App {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
                     if ui.button("Save").clicked() {
-                        match write_savedata(&self.appconfig, &mut self.sm) {
-                            Ok(_) => self.show_ui_state.error_msg = "".to_string(),
-                            Err(e) => self.show_ui_state.error_msg = format!("{}", e)
-                        }
+                        let sc = savesections::SectionContext { appconfig: &self.appconfig, lm: &self.lm, arm: &self.arm, rd: &self.rd };
+                        let problems = self.sections.validate(&sc);
+                        if !problems.is_empty() {
+                            self.show_ui_state.error_msg = format!("Not saved. {}", problems.join("; "));
+                        } else {
+                            match write_savedata(&self.appconfig, &mut self.sm) {
+                                Ok(_) => self.show_ui_state.error_msg = "".to_string(),
+                                Err(e) => self.show_ui_state.error_msg = format!("{}", e)
+                            }
+                        };
                         ui.close_menu();
                     };
                     if ui.button("Options").clicked() {
@@ -689,44 +1475,26 @@ impl App {
                         ui.close_menu();
 
                     };
+                    if ui.button("Apothecary reference").clicked() {
+                        self.show_ui_state.apoth_ref_window = !self.show_ui_state.apoth_ref_window;
+                        ui.close_menu();
+                    };
+                    if ui.button("Star rating report").clicked() {
+                        self.show_ui_state.star_rating_report_window = !self.show_ui_state.star_rating_report_window;
+                        ui.close_menu();
+                    };
                     if ui.button("Give me 800 qty!").clicked() {
                         set_save_items_qty_800(&mut self.sm, &self.lm, Some(&self.arm));
-                        self.update_allitems_fromref();
                         ui.close_menu();
 
                     };
                     if ui.button("Give me 100 qty in larder").clicked() {
                         set_larders_qty_100(&mut self.sm, &self.lm, Some(&self.arm));
-                        self.update_allitems_fromref();
                         ui.close_menu();
                     };
-                    if ui.button("Sort by type, name").clicked() {
-                        self.save_inventory_items.sort_by(|a,b| 
-                            {let first = a.pickup_type_name.cmp(&b.pickup_type_name);
-                            let second = a.name.cmp(&b.name);
-                            first.then(second)}
-                        );
-                        ui.close_menu();
-                    };
-                    if ui.button("Sort by cost, name").clicked() {
-                        self.save_inventory_items.sort_by(|a,b| 
-                            {let first = b.cost.cmp(&a.cost);
-                            let second = a.name.cmp(&b.name);
-                            first.then(second)}
-                        );
-                        ui.close_menu();
-                    };
-                    if ui.button("Sort by UID").clicked() {
-                        self.save_inventory_items.sort_by(|a,b| 
-                            {a.uid.cmp(&b.uid)}
-                        );
-                        ui.close_menu();
-                    };
-                    if ui.button("Sort by name").clicked() {
-                        self.save_inventory_items.sort_by(|a,b| 
-                            {a.name.cmp(&b.name)}
-                        );
-                        ui.close_menu();
+                    let sc = savesections::SectionContext { appconfig: &self.appconfig, lm: &self.lm, arm: &self.arm, rd: &self.rd };
+                    for section in self.sections.iter_mut() {
+                        section.menu_ui(ui, &mut self.sm, &sc);
                     };
                 });
                 if ui.button("Player data").clicked() {self.show_ui_state.player_data_window = !self.show_ui_state.player_data_window;};
@@ -734,189 +1502,60 @@ impl App {
                     self.show_ui_state.save_tree_window = !self.show_ui_state.save_tree_window;
                     ui.close_menu();
                 };
+                if ui.button("Quick edits").clicked() {
+                    self.show_ui_state.quick_edits_window = !self.show_ui_state.quick_edits_window;
+                };
+                if ui.button("Save overview").clicked() {
+                    self.show_ui_state.save_overview_window = !self.show_ui_state.save_overview_window;
+                };
+                for section in self.sections.iter_placed_mut(savesections::SectionPlacement::Window) {
+                    if ui.button(section.name()).clicked() && !self.show_ui_state.section_windows.remove(section.name()) {
+                        self.show_ui_state.section_windows.insert(section.name());
+                    };
+                };
             });
         });
     }
 
+    pub fn section_windows(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let sc = savesections::SectionContext { appconfig: &self.appconfig, lm: &self.lm, arm: &self.arm, rd: &self.rd };
+        for section in self.sections.iter_placed_mut(savesections::SectionPlacement::Window) {
+            let name = section.name();
+            let mut b_open = self.show_ui_state.section_windows.contains(name);
+            if !b_open {continue};
+            egui::Window::new(name)
+                .open(&mut b_open)
+                .default_width(300.0)
+                .vscroll(true)
+                .show(ctx, |ui| {
+                    section.ui(ui, &mut self.sm, &sc, &mut self.show_ui_state.error_msg);
+                });
+            if !b_open {self.show_ui_state.section_windows.remove(name);};
+        };
+    }
+
     pub fn central_panel(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let sc = savesections::SectionContext { appconfig: &self.appconfig, lm: &self.lm, arm: &self.arm, rd: &self.rd };
         egui::CentralPanel::default().show(ctx, |ui| {
-            use egui_extras::{Column, TableBuilder};
-
-            let table = TableBuilder::new(ui)
-                .striped(true)
-                .resizable(false)
-                .cell_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown))
-                .column(Column::initial(40.0).at_least(40.0))
-                .column(Column::initial(65.0).at_least(65.0))
-                .column(Column::initial(65.0).at_least(65.0))
-                .column(Column::initial(65.0).at_least(65.0))
-                .column(Column::initial(65.0).at_least(65.0))
-                .column(Column::initial(65.0).at_least(65.0))
-                .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
-                .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
-                .column(Column::initial(40.0).at_least(40.0))
-                .column(Column::initial(20.0).at_least(20.0))
-                .column(Column::initial(20.0).at_least(20.0))
-                .min_scrolled_height(0.0);
-
-            table
-                .header(20.0, |mut header| {
-                    header.col(|ui| {
-                        ui.strong("uid");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Qty, Star 1");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Qty, Star 2");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Qty, Star 3");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Qty, Star 4");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Qty, Star 5");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Name");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Type");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Cost");
-                    });
-                    header.col(|ui| {
-                        ui.strong("");
-                    });
-                    header.col(|ui| {
-                        ui.strong("");
-                    });
-                })
-                .body(|body| {
-                    let row_height = 30.0;
-                    let num_rows = self.save_inventory_items.len();
-                    body.rows(row_height, num_rows, |mut row| {
-                        let row_index = row.index();
-                        row.col(|ui| {
-                            // ui.label(self.save_inventory_items[row_index].uid.to_string());
-
-                            let dragvalue_response = ui.add(egui::DragValue::new(&mut self.save_inventory_items[row_index].uid));
-                            if dragvalue_response.changed() {
-                                let new_uid = self.save_inventory_items[row_index].save_item_ref.set_uid(
-                                    &mut self.sm, 
-                                    self.save_inventory_items[row_index].uid);
-                                self.save_inventory_items[row_index].uid = new_uid;
-                                self.save_inventory_items[row_index].name = self.lm.full_item_lookup[&new_uid].name.clone();
-                                self.save_inventory_items[row_index].pickup_type_name = self.lm.pickup_type_lookup_rev[&self.lm.full_item_lookup[&new_uid].type_of_pickup].clone();
-                                self.save_inventory_items[row_index].cost = self.lm.full_item_lookup[&new_uid].cost;
-
-                                if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                    x.update_all_strings(&self.sm.xtree);
-                                };
-                            };
-                        });
-                        for count_index in 0..5 {
-                            row.col(|ui| {
-                                let dragvalue_response = ui.add(egui::DragValue::new(&mut self.save_inventory_items[row_index].counts[count_index].count));
-                                if dragvalue_response.changed() {
-                                    let new_count = self.save_inventory_items[row_index].save_item_ref.set_count_at_idx(
-                                        count_index, 
-                                        self.save_inventory_items[row_index].counts[count_index].count, 
-                                        &mut self.sm, 
-                                        Some(&self.lm));
-                                    self.save_inventory_items[row_index].counts[count_index].count = new_count;
-
-                                    if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                        x.update_all_strings(&self.sm.xtree);
-                                    };
-                                };
-                            });
-                        };
-                        row.col(|ui| {
-                            ui.label(self.save_inventory_items[row_index].name.clone());
-                        });
-                        row.col(|ui| {
-                            ui.label(self.save_inventory_items[row_index].pickup_type_name.clone());
-                        });
-                        row.col(|ui| {
-                            ui.label(self.save_inventory_items[row_index].cost.to_string());
-                        });
-                        row.col(|ui| {
-                            if ui.add_sized([20.0, 20.0], egui::Button::new("+")).clicked() {
-                                let result_copy_new = self.save_inventory_items[row_index].save_item_ref.copy_new(&mut self.sm, savedata::LocationItemRef::Inventory);
-                                match result_copy_new {
-                                    Ok(siir) => {
-                                        self.save_inventory_items.insert(row_index, AppSaveInventoryItem::new(&siir, &self.sm, &self.lm));
-                                        if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                            x.reload_data(&self.sm.xtree);
-                                        };
-                                    },
-                                    Err(_e) => {
-                                        self.show_ui_state.error_msg.push_str("Error unable to copy to new inventory item."); 
-                                    }
-                                }
-                            };
-                        });
-                        row.col(|ui| {
-                            if ui.add_sized([20.0, 20.0], egui::Button::new("-")).clicked() {
-                                let result_remove = self.save_inventory_items[row_index].save_item_ref.remove(&mut self.sm, savedata::LocationItemRef::Inventory);
-                                match result_remove {
-                                    Ok(_) => {
-                                        self.save_inventory_items.remove(row_index);
-                                        if let Some(x) = &mut self.sm.save_tree { // todo: remove invtree, appsaveitem, playerdata coupling
-                                            x.reload_data(&self.sm.xtree);
-                                        };
-                                    },
-                                    Err(_e) => {
-                                        self.show_ui_state.error_msg.push_str("Error unable to remove inventory item."); 
-                                    }
-                                }
-                            };
-                        });
-                    })
-                })
+            for section in self.sections.iter_placed_mut(savesections::SectionPlacement::CentralPanel) {
+                section.ui(ui, &mut self.sm, &sc, &mut self.show_ui_state.error_msg);
+            };
         });
     }
 
-    pub fn update_allitems_fromref(&mut self) {
-        self.save_inventory_items.iter_mut().for_each(|x| x.update_fromref(&self.sm, &self.lm));
-    }
-
-    pub fn update_playerdata(player_data: &mut PlayerData, xtree: &xot::Xot, brass_count_node: &Option<xot::Node>,
-        stats_nodes: &Vec<xot::Node>, tool_level_ref: &Vec<savedata::ToolLevelRef>) 
-    {
-        player_data.brass = {
-            match xtree.text_content_str(brass_count_node.unwrap()) {
-                Some(x) => x.parse().unwrap_or_else(|_| {0}),
-                _ => {0}
-            }
-        };
-
-        for (idx, item) in stats_nodes.iter().enumerate() {
-            let stat_val_str = xtree.text_content_str(*item);
-            for stat_item in player_data.stats.iter_mut() {
-                if stat_val_str.is_some() && stat_item.0 == idx {
-                    let stat_val: u8 = stat_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                    stat_item.2 = stat_val;
-                }
-            }
-        };
-
-        for (idx, item) in tool_level_ref.iter().enumerate() {
-            let tool_level_val_str = xtree.text_content_str(item.tool_level_node);
-            let tool_xp_val_str = xtree.text_content_str(item.tool_current_xp_node);
-            for tool_level_item in player_data.tool_level.iter_mut() {
-                if tool_level_val_str.is_some() && tool_xp_val_str.is_some() && tool_level_item.0 == idx {
-                    let tool_level_val: u8 = tool_level_val_str.unwrap().parse().unwrap_or_else(|_| {0});
-                    let tool_xp_val: f32 = tool_xp_val_str.unwrap().parse().unwrap_or_else(|_| {0.0});
-                    tool_level_item.2 = tool_level_val;
-                    tool_level_item.3 = tool_xp_val;
-                }
-            }
+    /// Hands the changes made to the save this frame to the views holding save nodes, see savechanges::SaveChanges.
+    pub fn dispatch_save_changes(&mut self) {
+        let events = self.sm.take_changes();
+        if events.is_empty() {return};
+        // edited values can change the types inferred from them
+        self.show_ui_state.save_tree_view_state.value_types.clear();
+        // taken out of sm while it's updated from the rest of the save data
+        if let Some(mut save_tree) = self.sm.save_tree.take() {
+            save_tree.on_save_changes(&self.sm, &self.lm, &events);
+            self.sm.save_tree = Some(save_tree);
         };
+        self.sections.on_save_changes(&self.sm, &self.lm, &events);
+        self.show_ui_state.save_survey.on_save_changes(&self.sm, &self.lm, &events);
     }
 
 }
@@ -930,10 +1569,10 @@ impl eframe::App for App {
             appconfig: _,
             lm: _,
             sm: _,
-            save_inventory_items: _,
+            sections: _,
             arm: _,
+            rd: _,
             show_ui_state: _,
-            player_data: _,
         } = self;
         
         if self.show_ui_state.top_panel {self.top_panel(ctx, frame)};
@@ -941,107 +1580,206 @@ impl eframe::App for App {
         if self.show_ui_state.central_panel {self.central_panel(ctx, frame)};
         if self.show_ui_state.options_window {self.options_window(ctx, frame)};
         if self.show_ui_state.loot_ref_window {self.loot_ref_window(ctx, frame)};
+        if self.show_ui_state.apoth_ref_window {self.apoth_ref_window(ctx, frame)};
+        if self.show_ui_state.star_rating_report_window {self.star_rating_report_window(ctx, frame)};
         if self.show_ui_state.player_data_window {self.player_data_window(ctx, frame)};
         if self.show_ui_state.save_tree_window {self.save_tree_window(ctx, frame)};
+        if self.show_ui_state.quick_edits_window {self.quick_edits_window(ctx, frame)};
+        if self.show_ui_state.save_overview_window {self.save_overview_window(ctx, frame)};
+        if !self.show_ui_state.section_windows.is_empty() {self.section_windows(ctx, frame)};
+        self.dispatch_save_changes();
 
     }
 }
 
 
-#[derive(Debug)]
-pub struct SaveInventoryItemCount {
-    pub count: i32,
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum LootRefColumn {
+    Uid,
+    Name,
+    Type,
+    Cost,
+    StarRating,
+    HasStarRating,
+    IsLiquid,
+    IsHideQuantity,
+    MaxQty,
+    CureStatus,
+    IsCarryable,
+    GrowablePresetIdx,
+    SpriteIdx,
+    InGameSpriteIdx,
+    ProverbSprite,
 }
 
-impl SaveInventoryItemCount {
-
-    pub fn new(count: i32) -> Self {
-        Self {
-            count,
+impl LootRefColumn {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Uid => "uid",
+            Self::Name => "Name",
+            Self::Type => "Type",
+            Self::Cost => "Cost",
+            Self::StarRating => "Star rating",
+            Self::HasStarRating => "Has stars",
+            Self::IsLiquid => "Liquid",
+            Self::IsHideQuantity => "Hide qty",
+            Self::MaxQty => "Max qty",
+            Self::CureStatus => "Cure",
+            Self::IsCarryable => "Carryable",
+            Self::GrowablePresetIdx => "Growable preset",
+            Self::SpriteIdx => "Sprite",
+            Self::InGameSpriteIdx => "In-game sprite",
+            Self::ProverbSprite => "Proverb sprite",
         }
     }
-
 }
 
-pub struct AppSaveInventoryItem {
-    pub save_item_ref: savedata::SaveInventoryItemRef,
-    pub uid: i32,
-    pub counts: [SaveInventoryItemCount; 5],
-    pub name: String,
-    pub pickup_type_name: String,
-    pub cost: i32,
-    // sprite_idx: i32
+/// Lowercased name and type filters, has stars/liquid/hide qty/carryable filters, cure filter, sort column and descending.
+type LootRefFilterKey = (String, String, [Option<bool>; 4], Option<apothrecipes::CureStatus>, LootRefColumn, bool);
+
+/// Rows of the loot reference window, built once per data load, and the rows shown for the current filters and sort.
+#[derive(Default)]
+pub struct LootRefView {
+    rows: Vec<LootRefRow>,
+    /// indices into rows, in display order
+    shown: Vec<usize>,
+    /// filters and sort shown was made for, None when it needs making
+    shown_key: Option<LootRefFilterKey>,
 }
 
-impl Hash for AppSaveInventoryItem {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.save_item_ref.key_int_node.hash(state);
+impl LootRefView {
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.shown.clear();
+        self.shown_key = None;
+    }
+
+    fn update_shown(&mut self, filter_key: LootRefFilterKey) {
+        let (name_filter, type_filter, [star_rating_filter, liquid_filter, hide_quantity_filter, carryable_filter], cure_filter, sort_column, b_descending) = &filter_key;
+        let tristate_match = |filter: &Option<bool>, value: bool| filter.is_none() || *filter == Some(value);
+        let rows = &self.rows;
+        self.shown = (0..rows.len()).filter(|idx| {
+            let v = &rows[*idx];
+            (name_filter.is_empty() || v.item.name.to_lowercase().contains(name_filter))
+            && (type_filter.is_empty() || v.pickup_type_name.to_lowercase().contains(type_filter))
+            && tristate_match(star_rating_filter, v.has_star_rating)
+            && tristate_match(liquid_filter, v.is_liquid)
+            && tristate_match(hide_quantity_filter, v.is_hide_quantity)
+            && tristate_match(carryable_filter, v.is_carryable())
+            && (cure_filter.is_none() || *cure_filter == v.cure_status)
+        }).collect();
+        self.shown.sort_by(|a, b| rows[*a].cmp_by(&rows[*b], *sort_column).then(rows[*a].item.uid.cmp(&rows[*b].item.uid)));
+        if *b_descending {self.shown.reverse()};
+        self.shown_key = Some(filter_key);
     }
 }
 
-impl AppSaveInventoryItem {
+/// A LootItem with the data derived from it by LootManager and ApothRecipeManager, for the loot reference window.
+pub struct LootRefRow {
+    pub item: lootitems::LootItem,
+    pub pickup_type_name: String,
+    pub has_star_rating: bool,
+    pub star_rating_source: lootitems::StarRatingSource,
+    pub is_liquid: bool,
+    pub is_hide_quantity: bool,
+    pub max_qty: [i32; 5],
+    pub cure_status: Option<apothrecipes::CureStatus>,
+}
 
-    pub fn new(save_item_ref: &savedata::SaveInventoryItemRef, sm: &savedata::SaveDataManager, lm: &lootitems::LootManager) -> Self {
-        let sir = save_item_ref.clone();
-        let uid = save_item_ref.get_uid(sm);
-        let counts = save_item_ref.get_counts(sm).map(SaveInventoryItemCount::new);
-        let li = save_item_ref.get_lootitem_ref(sm,lm);
-        let li_name = li.name.to_string();
-        let li_pickup_type = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
+impl LootRefRow {
 
+    pub fn new(item: &lootitems::LootItem, lm: &lootitems::LootManager, arm: &apothrecipes::ApothRecipeManager) -> Self {
+        let star_rating_explanation = lm.explain_star_rating(item.uid);
         Self {
-            save_item_ref: sir,
-            uid,
-            counts,
-            name: li_name,
-            pickup_type_name: li_pickup_type,
-            cost: li.cost,
-            // sprite_idx: li.sprite_idx
+            item: item.clone(),
+            pickup_type_name: lm.pickup_type_lookup_rev.get(&item.type_of_pickup).cloned().unwrap_or_default(),
+            has_star_rating: star_rating_explanation.has_star_rating,
+            star_rating_source: star_rating_explanation.source,
+            is_liquid: lm.is_liquid_item(item.uid),
+            is_hide_quantity: lm.is_hide_quantity_item(item.uid),
+            max_qty: lm.get_max_item_quantity(item.uid),
+            cure_status: arm.get_cure_status(item.uid),
         }
     }
 
-    pub fn new_xt(save_item_ref: &savedata::SaveInventoryItemRef, xtree: &xot::Xot, lm: &lootitems::LootManager) -> Self {
-        let sir = save_item_ref.clone();
-        let uid = sir.get_uid_xt(xtree);
-        let counts = sir.get_counts_xt(xtree).map(SaveInventoryItemCount::new);
-        let li = sir.get_lootitem_ref_xt(xtree,lm);
-        let li_name = li.name.to_string();
-        let li_pickup_type = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
-        let li_cost = li.cost;
+    pub fn is_carryable(&self) -> bool {
+        self.item.is_carryable.eq_ignore_ascii_case("true")
+    }
 
-        Self {
-            save_item_ref: sir,
-            uid,
-            counts,
-            name: li_name,
-            pickup_type_name: li_pickup_type,
-            cost: li_cost,
-            // sprite_idx: li.sprite_idx
+    pub fn cell_text(&self, col: LootRefColumn) -> String {
+        match col {
+            LootRefColumn::Uid => self.item.uid.to_string(),
+            LootRefColumn::Name => self.item.name.clone(),
+            LootRefColumn::Type => self.pickup_type_name.clone(),
+            LootRefColumn::Cost => self.item.cost.to_string(),
+            LootRefColumn::StarRating => self.item.star_rating.to_string(),
+            LootRefColumn::HasStarRating => self.has_star_rating.to_string(),
+            LootRefColumn::IsLiquid => self.is_liquid.to_string(),
+            LootRefColumn::IsHideQuantity => self.is_hide_quantity.to_string(),
+            LootRefColumn::MaxQty => self.max_qty.map(|x| x.to_string()).join("/"),
+            LootRefColumn::CureStatus => match self.cure_status {
+                Some(x) => format!("{:?}", x),
+                None => "".to_string(),
+            },
+            LootRefColumn::IsCarryable => self.item.is_carryable.clone(),
+            LootRefColumn::GrowablePresetIdx => self.item.growable_preset_idx.to_string(),
+            LootRefColumn::SpriteIdx => self.item.sprite_idx.to_string(),
+            LootRefColumn::InGameSpriteIdx => self.item.in_game_sprite_idx.to_string(),
+            LootRefColumn::ProverbSprite => self.item.proverb_sprite.to_string(),
         }
     }
 
-    pub fn update_fromref(&mut self, sm: &savedata::SaveDataManager, lm: &lootitems::LootManager) {
-        let sir = &self.save_item_ref;
-        self.uid = sir.get_uid(sm);
-        self.counts = sir.get_counts(sm).map(SaveInventoryItemCount::new);
-        let li = sir.get_lootitem_ref(sm,lm);
-        self.name = li.name.to_string();
-        self.pickup_type_name = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
-        self.cost = li.cost;
+    pub fn cmp_by(&self, other: &Self, col: LootRefColumn) -> std::cmp::Ordering {
+        match col {
+            LootRefColumn::Uid => self.item.uid.cmp(&other.item.uid),
+            LootRefColumn::Name => self.item.name.cmp(&other.item.name),
+            LootRefColumn::Type => self.pickup_type_name.cmp(&other.pickup_type_name),
+            LootRefColumn::Cost => self.item.cost.cmp(&other.item.cost),
+            LootRefColumn::StarRating => self.item.star_rating.cmp(&other.item.star_rating),
+            LootRefColumn::HasStarRating => self.has_star_rating.cmp(&other.has_star_rating),
+            LootRefColumn::IsLiquid => self.is_liquid.cmp(&other.is_liquid),
+            LootRefColumn::IsHideQuantity => self.is_hide_quantity.cmp(&other.is_hide_quantity),
+            LootRefColumn::MaxQty => self.max_qty.cmp(&other.max_qty),
+            LootRefColumn::CureStatus => self.cure_status.map(|x| x as i32).cmp(&other.cure_status.map(|x| x as i32)),
+            LootRefColumn::IsCarryable => self.is_carryable().cmp(&other.is_carryable()),
+            LootRefColumn::GrowablePresetIdx => self.item.growable_preset_idx.cmp(&other.item.growable_preset_idx),
+            LootRefColumn::SpriteIdx => self.item.sprite_idx.cmp(&other.item.sprite_idx),
+            LootRefColumn::InGameSpriteIdx => self.item.in_game_sprite_idx.cmp(&other.item.in_game_sprite_idx),
+            LootRefColumn::ProverbSprite => self.item.proverb_sprite.cmp(&other.item.proverb_sprite),
+        }
     }
+}
 
-    pub fn update_fromref_xt(&mut self, xtree: &xot::Xot, lm: &lootitems::LootManager) {
-        let sir = &self.save_item_ref;
-        self.uid = sir.get_uid_xt(xtree);
-        self.counts = sir.get_counts_xt(xtree).map(SaveInventoryItemCount::new);
-        let li = sir.get_lootitem_ref_xt(xtree,lm);
-        self.name = li.name.to_string();
-        self.pickup_type_name = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
-        self.cost = li.cost;
-    }
+/// Any/Yes/No combo box for filtering on a bool.
+pub fn tristate_filter_ui(ui: &mut egui::Ui, label: &str, filter: &mut Option<bool>) {
+    let text = |x: &Option<bool>| match x {
+        None => "Any",
+        Some(true) => "Yes",
+        Some(false) => "No",
+    };
+    egui::ComboBox::from_label(label)
+        .selected_text(text(filter))
+        .show_ui(ui, |ui| {
+            for option in [None, Some(true), Some(false)] {
+                ui.selectable_value(filter, option, text(&option));
+            };
+        });
+}
+
+pub fn cure_status_badge_ui(ui: &mut egui::Ui, cure_status: apothrecipes::CureStatus) -> egui::Response {
+    let (text, color) = match cure_status {
+        apothrecipes::CureStatus::FullCure => ("Full cure", egui::Color32::DARK_GREEN),
+        apothrecipes::CureStatus::PartialCure => ("Partial", egui::Color32::from_rgb(200, 160, 0)),
+        apothrecipes::CureStatus::FailureOrPlacebo => ("Fail/Placebo", egui::Color32::RED),
+        apothrecipes::CureStatus::SideEffect => ("Side effect", egui::Color32::from_rgb(220, 110, 0)),
+    };
+    ui.colored_label(color, text)
 }
 
 pub fn set_save_items_qty_800(sm: &mut savedata::SaveDataManager, lm: &lootitems::LootManager, arm: Option<&apothrecipes::ApothRecipeManager>) {
+    // written after the item refs are read, through set_node_text so the views get the changes
+    let mut writes: Vec<(xot::Node, i32)> = Vec::new();
     for item in sm.save_inventory_ref.iter() {
         // println!("{:?}", sm.xtree.text_content_str(item.key_int_node));
         let uid = sm.xtree.text_content_str(item.key_int_node).unwrap().parse::<i32>().unwrap();
@@ -1062,17 +1800,20 @@ pub fn set_save_items_qty_800(sm: &mut savedata::SaveDataManager, lm: &lootitems
         } 
 
         for (idx, count_ref) in item.count_int_nodes.iter().enumerate() {
-            let count_text = sm.xtree.text_content_mut(*count_ref).unwrap();
             let compare_nums = [max_qty[idx], 800, arm_max_qty];
             let new_qty = compare_nums.iter().min().unwrap();
-            // println!("old qty {}, new qty {}", count_text.get(), new_qty);
-            count_text.set(new_qty.to_string());
+            writes.push((*count_ref, *new_qty));
         };
     };
+    for (count_ref, new_qty) in writes {
+        sm.set_node_text(count_ref, new_qty.to_string());
+    };
 }
 
 pub fn set_larders_qty_100(sm: &mut savedata::SaveDataManager, lm: &lootitems::LootManager, arm: Option<&apothrecipes::ApothRecipeManager>) {
-    
+    // written after the item refs are read, through set_node_text so the views get the changes
+    let mut writes: Vec<(xot::Node, i32)> = Vec::new();
+
     if !sm.newlarder_item_ref.is_empty() {
         for item in sm.newlarder_item_ref.iter() {
             // println!("{:?}", sm.xtree.text_content_str(item.key_int_node));
@@ -1102,11 +1843,9 @@ pub fn set_larders_qty_100(sm: &mut savedata::SaveDataManager, lm: &lootitems::L
             } 
 
             for (idx, count_ref) in item.count_int_nodes.iter().enumerate() {
-                let count_text = sm.xtree.text_content_mut(*count_ref).unwrap();
                 let compare_nums = [max_qty[idx], 100, arm_max_qty];
                 let new_qty = compare_nums.iter().min().unwrap();
-                // println!("homelarder old qty {}, new qty {}", count_text.get(), new_qty);
-                count_text.set(new_qty.to_string());
+                writes.push((*count_ref, *new_qty));
             };
         };
     };
@@ -1140,15 +1879,16 @@ pub fn set_larders_qty_100(sm: &mut savedata::SaveDataManager, lm: &lootitems::L
             } 
 
             for (idx, count_ref) in item.count_int_nodes.iter().enumerate() {
-                let count_text = sm.xtree.text_content_mut(*count_ref).unwrap();
                 let compare_nums = [max_qty[idx], 100, arm_max_qty];
                 let new_qty = compare_nums.iter().min().unwrap();
-                // println!("shops old qty {}, new qty {}", count_text.get(), new_qty);
-                count_text.set(new_qty.to_string());
+                writes.push((*count_ref, *new_qty));
             };
         };
     };
 
+    for (count_ref, new_qty) in writes {
+        sm.set_node_text(count_ref, new_qty.to_string());
+    };
 }
 
 pub fn get_dupe_uids(sm: &savedata::SaveDataManager, lir: savedata::LocationItemRef) -> Vec<i32> {
@@ -0,0 +1,220 @@
+use std::error::Error;
+
+use strum::IntoEnumIterator;
+
+use crate::app;
+use crate::config;
+use crate::savedata;
+use crate::savemodel;
+use crate::savesurvey;
+
+const USAGE: &str = "Usage:
+  kynseed_saveeditor                      start the editor
+  kynseed_saveeditor <save file>          start the editor on that save, same as --save
+  kynseed_saveeditor get <path>           print the value of every node matching path
+  kynseed_saveeditor set <path> <value>   set every leaf node matching path, and save, if value suits the type of each
+  kynseed_saveeditor check                read the player data, print a summary or every value that doesn't parse
+  kynseed_saveeditor survey               list the top level parts of the save by what they're probably about
+
+Options (each also set by an environment variable, options take priority):
+  --config <file>                         config file to use instead of ./saveedit_appconfig.toml   KYNSEED_CONFIG
+  --profile <name>                        use the named profile of the config file instead of the active one   KYNSEED_PROFILE
+  --saves-dir <folder>                    path_kynseed_saves   KYNSEED_SAVES_DIR
+  --data-dir <folder>                     path_kynseed_data   KYNSEED_DATA_DIR
+  --saveedit-data-dir <folder>            path_saveedit_data   KYNSEED_SAVEEDIT_DATA_DIR
+  --save <file>                           filename_kynseed_save, and path_kynseed_saves if file has a folder   KYNSEED_SAVE
+  --embedded-data <true|false>            b_use_embedded_saveedit_data   KYNSEED_EMBEDDED_DATA
+  
+  path is a save query e.g. PlayerData/Inventory/AllItems/item[key/int=512]/value/InventoryItem/Count/int[3]";
+
+#[derive(Debug, Clone)]
+pub struct CliError(pub String);
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for CliError {}
+
+const COMMANDS: [&str; 7] = ["get", "set", "check", "survey", "help", "--help", "-h"];
+
+/// Takes the options (for the editor and every command, given anywhere on the command line) out of args,
+/// returns them as overrides with the remaining command and arguments. A lone save file path, e.g. from a
+/// file association, is the same as --save. Anything else that isn't a command is an error.
+pub fn parse_options(args: &[String]) -> Result<(config::ConfigOverrides, Vec<String>), CliError> {
+    let mut overrides = config::ConfigOverrides::default();
+    let mut rest: Vec<String> = Vec::new();
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        if !["--config", "--profile", "--saves-dir", "--data-dir", "--saveedit-data-dir", "--save", "--embedded-data"].contains(&flag) {
+            rest.push(arg.clone());
+            continue;
+        };
+        let value = match inline_value {
+            Some(x) => x,
+            None => args_iter.next().cloned().ok_or_else(|| CliError(format!("{} needs a value\n\n{}", flag, USAGE)))?,
+        };
+        match flag {
+            "--config" => overrides.config_filepath = Some(value.into()),
+            "--profile" => overrides.profile = Some(value),
+            "--saves-dir" => overrides.path_kynseed_saves = Some(value),
+            "--data-dir" => overrides.path_kynseed_data = Some(value),
+            "--saveedit-data-dir" => overrides.path_saveedit_data = Some(value),
+            "--save" => overrides.set_save_filepath(&value),
+            _ => overrides.b_use_embedded_saveedit_data = Some(config::parse_bool(flag, &value).map_err(|e| CliError(e.0))?),
+        };
+    };
+    match &rest[..] {
+        [] => {},
+        [command, ..] if COMMANDS.contains(&command.as_str()) => {},
+        [save_filepath] if std::path::Path::new(save_filepath).is_file() => {
+            overrides.set_save_filepath(save_filepath);
+            rest.clear();
+        },
+        [arg, ..] => return Err(CliError(format!("{} is not a command, option or save file\n\n{}", arg, USAGE))),
+    };
+    Ok((overrides, rest))
+}
+
+/// Runs a command line command, returns None if args don't contain one and the editor should start.
+pub fn run(args: &[String]) -> Option<Result<(), Box<dyn Error>>> {
+    match args.first().map(|x| x.as_str()) {
+        Some("get") => Some(run_get(&args[1..])),
+        Some("set") => Some(run_set(&args[1..])),
+        Some("check") => Some(run_check(&args[1..])),
+        Some("survey") => Some(run_survey(&args[1..])),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Some(Ok(()))
+        },
+        _ => None,
+    }
+}
+
+fn load_savedata(appconfig: &config::AppConfig) -> Result<savedata::SaveDataManager, Box<dyn Error>> {
+    let mut sm = savedata::SaveDataManager::default();
+    sm.load_data(appconfig)?;
+    Ok(sm)
+}
+
+fn run_get(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else {
+        return Err(Box::new(CliError(USAGE.to_string())));
+    };
+    let appconfig = config::load_appconfig()?;
+    let sm = load_savedata(&appconfig)?;
+    for (node_path, text) in sm.query_get_strs(path)? {
+        println!("{} = {}", node_path, text);
+    };
+    Ok(())
+}
+
+fn run_set(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path, value] = args else {
+        return Err(Box::new(CliError(USAGE.to_string())));
+    };
+    let appconfig = config::load_appconfig()?;
+    let mut sm = load_savedata(&appconfig)?;
+    let set_count = sm.query_set_str(path, value)?;
+    if set_count == 0 {
+        return Err(Box::new(CliError(format!("No leaf nodes match {}", path))));
+    };
+    app::write_savedata(&appconfig, &mut sm)?;
+    println!("Set {} nodes.", set_count);
+    Ok(())
+}
+
+fn run_check(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if !args.is_empty() {
+        return Err(Box::new(CliError(USAGE.to_string())));
+    };
+    let appconfig = config::load_appconfig()?;
+    let sm = load_savedata(&appconfig)?;
+    let player_data = savemodel::PlayerData::extract(&sm)?;
+    println!("Brass {}", player_data.brass);
+    for stat in player_data.stats.iter() {
+        println!("{} {}", stat.name, stat.value);
+    };
+    for tool in player_data.tools.iter() {
+        println!("{} level {}, xp {}", tool.tool_type, tool.level, tool.current_xp);
+    };
+    println!("{} inventory items, {} larder items, {} shop items", 
+        player_data.inventory.len(), player_data.larder.len(), player_data.shops.len());
+    Ok(())
+}
+
+fn run_survey(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if !args.is_empty() {
+        return Err(Box::new(CliError(USAGE.to_string())));
+    };
+    let appconfig = config::load_appconfig()?;
+    let sm = load_savedata(&appconfig)?;
+    let mut survey = savesurvey::SaveSurvey::default();
+    survey.refresh(&sm);
+    for category in savesurvey::SurveyCategory::iter() {
+        let entries: Vec<&savesurvey::SurveyEntry> = survey.entries_in(category).collect();
+        if entries.is_empty() {continue};
+        println!("{}", category.label());
+        for entry in entries {
+            println!("  {} ({})", entry.path, entry.shape.summary());
+        };
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options_anywhere_on_the_command_line() {
+        let (overrides, rest) = parse_options(&args(&["get", "--profile", "modded", "PlayerData/Brass", "--data-dir=D:/Data"])).unwrap();
+        assert_eq!(rest, args(&["get", "PlayerData/Brass"]));
+        assert_eq!(overrides.profile.as_deref(), Some("modded"));
+        assert_eq!(overrides.path_kynseed_data.as_deref(), Some("D:/Data"));
+        assert!(overrides.config_filepath.is_none() && overrides.path_kynseed_saves.is_none());
+    }
+
+    #[test]
+    fn parse_options_save_with_folder_sets_saves_folder() {
+        let (overrides, rest) = parse_options(&args(&["--save", "Saves/Slot2_Autosave.xml", "--embedded-data", "off"])).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(overrides.path_kynseed_saves.as_deref(), Some("Saves"));
+        assert_eq!(overrides.filename_kynseed_save.as_deref(), Some("Slot2_Autosave.xml"));
+        assert_eq!(overrides.b_use_embedded_saveedit_data, Some(false));
+
+        let (overrides, _) = parse_options(&args(&["--save=Slot3.xml", "--config=other.toml"])).unwrap();
+        assert!(overrides.path_kynseed_saves.is_none());
+        assert_eq!(overrides.filename_kynseed_save.as_deref(), Some("Slot3.xml"));
+        assert_eq!(overrides.config_filepath, Some(std::path::PathBuf::from("other.toml")));
+    }
+
+    #[test]
+    fn parse_options_lone_save_file_is_the_same_as_save() {
+        let save_filepath = std::env::temp_dir().join("kynseed_saveeditor_cli_test_save.xml");
+        std::fs::write(&save_filepath, "<SaveGame/>").unwrap();
+        let (overrides, rest) = parse_options(&[save_filepath.to_string_lossy().to_string()]).unwrap();
+        std::fs::remove_file(&save_filepath).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(overrides.filename_kynseed_save.as_deref(), Some("kynseed_saveeditor_cli_test_save.xml"));
+    }
+
+    #[test]
+    fn parse_options_rejects_bad_input() {
+        let error = |x: &[&str]| parse_options(&args(x)).unwrap_err().0;
+        assert!(error(&["--profile"]).starts_with("--profile needs a value"));
+        assert!(error(&["--embedded-data", "maybe"]).contains("is not true or false"));
+        assert!(error(&["no_such_save.xml"]).starts_with("no_such_save.xml is not a command, option or save file"));
+        assert!(error(&["--verbose", "get"]).starts_with("--verbose is not a command"));
+    }
+}
@@ -1,15 +1,259 @@
+use std::collections::BTreeMap;
+use std::error::Error;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use serde::{Serialize, Deserialize};
 
+/// Name of the settings at the top level of the config file, used when no other profile is chosen.
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+
+#[derive(Debug, Clone)]
+pub struct ConfigError(pub String);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ConfigError {}
+
+// environment variables read by ConfigOverrides::from_env
+pub const ENV_CONFIG: &str = "KYNSEED_CONFIG";
+pub const ENV_PROFILE: &str = "KYNSEED_PROFILE";
+pub const ENV_SAVES_DIR: &str = "KYNSEED_SAVES_DIR";
+pub const ENV_DATA_DIR: &str = "KYNSEED_DATA_DIR";
+pub const ENV_SAVEEDIT_DATA_DIR: &str = "KYNSEED_SAVEEDIT_DATA_DIR";
+pub const ENV_SAVE: &str = "KYNSEED_SAVE";
+pub const ENV_EMBEDDED_DATA: &str = "KYNSEED_EMBEDDED_DATA";
+
+static CONFIG_OVERRIDES: OnceLock<ConfigOverrides> = OnceLock::new();
+
+/// # ConfigOverrides
+/// Settings given on the command line or by KYNSEED_* environment variables, for this run only.
+/// They're applied to every profile loaded, and never written to the config file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub config_filepath: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub path_kynseed_saves: Option<String>,
+    pub path_kynseed_data: Option<String>,
+    pub path_saveedit_data: Option<String>,
+    pub filename_kynseed_save: Option<String>,
+    pub b_use_embedded_saveedit_data: Option<bool>,
+}
+
+impl ConfigOverrides {
+
+    /// Overrides from the KYNSEED_* environment variables that are set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|x| !x.is_empty());
+        let mut overrides = Self {
+            config_filepath: var(ENV_CONFIG).map(PathBuf::from),
+            profile: var(ENV_PROFILE),
+            path_kynseed_saves: var(ENV_SAVES_DIR),
+            path_kynseed_data: var(ENV_DATA_DIR),
+            path_saveedit_data: var(ENV_SAVEEDIT_DATA_DIR),
+            ..Default::default()
+        };
+        if let Some(save_filepath) = var(ENV_SAVE) {
+            overrides.set_save_filepath(&save_filepath);
+        };
+        if let Some(value) = var(ENV_EMBEDDED_DATA) {
+            overrides.b_use_embedded_saveedit_data = Some(parse_bool(ENV_EMBEDDED_DATA, &value)?);
+        };
+        Ok(overrides)
+    }
+
+    /// Save file to open, a path with a folder also sets the saves folder.
+    pub fn set_save_filepath(&mut self, save_filepath: &str) {
+        let save_filepath = PathBuf::from(save_filepath);
+        match (save_filepath.parent(), save_filepath.file_name()) {
+            (Some(folder), Some(filename)) if !folder.as_os_str().is_empty() => {
+                self.path_kynseed_saves = Some(folder.to_string_lossy().to_string());
+                self.filename_kynseed_save = Some(filename.to_string_lossy().to_string());
+            },
+            _ => self.filename_kynseed_save = Some(save_filepath.to_string_lossy().to_string()),
+        };
+    }
+
+    /// These overrides, with fallback's filling the settings these don't set.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            config_filepath: self.config_filepath.or(fallback.config_filepath),
+            profile: self.profile.or(fallback.profile),
+            path_kynseed_saves: self.path_kynseed_saves.or(fallback.path_kynseed_saves),
+            path_kynseed_data: self.path_kynseed_data.or(fallback.path_kynseed_data),
+            path_saveedit_data: self.path_saveedit_data.or(fallback.path_saveedit_data),
+            filename_kynseed_save: self.filename_kynseed_save.or(fallback.filename_kynseed_save),
+            b_use_embedded_saveedit_data: self.b_use_embedded_saveedit_data.or(fallback.b_use_embedded_saveedit_data),
+        }
+    }
+
+    /// Uses these overrides for the rest of the run, only the first call has any effect.
+    pub fn install(self) {
+        let _ = CONFIG_OVERRIDES.set(self);
+    }
+
+    fn apply(&self, appconfig: &mut AppConfig) {
+        if let Some(x) = &self.path_kynseed_saves {appconfig.path_kynseed_saves = x.clone()};
+        if let Some(x) = &self.path_kynseed_data {appconfig.path_kynseed_data = x.clone()};
+        if let Some(x) = &self.path_saveedit_data {appconfig.path_saveedit_data = x.clone()};
+        if let Some(x) = &self.filename_kynseed_save {appconfig.filename_kynseed_save = x.clone()};
+        if let Some(x) = self.b_use_embedded_saveedit_data {appconfig.b_use_embedded_saveedit_data = x};
+    }
+
+    /// Puts back stored's value of every overridden setting, so overrides aren't stored.
+    fn restore(&self, appconfig: &mut AppConfig, stored: &AppConfig) {
+        if self.path_kynseed_saves.is_some() {appconfig.path_kynseed_saves = stored.path_kynseed_saves.clone()};
+        if self.path_kynseed_data.is_some() {appconfig.path_kynseed_data = stored.path_kynseed_data.clone()};
+        if self.path_saveedit_data.is_some() {appconfig.path_saveedit_data = stored.path_saveedit_data.clone()};
+        if self.filename_kynseed_save.is_some() {appconfig.filename_kynseed_save = stored.filename_kynseed_save.clone()};
+        if self.b_use_embedded_saveedit_data.is_some() {appconfig.b_use_embedded_saveedit_data = stored.b_use_embedded_saveedit_data};
+    }
+}
+
+/// Accepts true/false, yes/no, on/off and 1/0.
+pub fn parse_bool(name: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Ok(true),
+        "false" | "no" | "off" | "0" => Ok(false),
+        _ => Err(ConfigError(format!("{} {:?} is not true or false", name, value))),
+    }
+}
+
+/// Overrides installed for this run, none if ConfigOverrides::install wasn't called.
+pub fn config_overrides() -> &'static ConfigOverrides {
+    CONFIG_OVERRIDES.get_or_init(ConfigOverrides::default)
+}
+
 pub fn get_config_filepath() -> PathBuf {
+    if let Some(config_filepath) = &config_overrides().config_filepath {
+        return config_filepath.clone();
+    };
     // let exe_path = std::env::current_exe().unwrap();
     // let config_folder = exe_path.parent().unwrap();
     let config_folder = PathBuf::from("./");
     config_folder.join("saveedit_appconfig.toml")
 }
 
+/// Loads the config file. A config file given by --config or KYNSEED_CONFIG that can't be loaded is an error,
+/// the default ./saveedit_appconfig.toml is replaced with the default config file instead.
+pub fn load_config_file() -> Result<ConfigFile, ConfigError> {
+    let config_filepath = get_config_filepath();
+    match confy::load_path(config_filepath.as_path()) {
+        Ok(cfg) => Ok(cfg),
+        Err(e) if config_overrides().config_filepath.is_some() => {
+            Err(ConfigError(format!("Could not load config file {}. {}", config_filepath.display(), e)))
+        },
+        Err(_e) => {
+            confy::store_path(config_filepath.as_path(), ConfigFile::default())
+                .map_err(|e| ConfigError(format!("Could not write default config file {}. {}", config_filepath.display(), e)))?;
+            Ok(ConfigFile::default())
+        }
+    }
+}
+
+pub fn store_config_file(config_file: &ConfigFile) -> Result<(), confy::ConfyError> {
+    let config_filepath = get_config_filepath();
+    confy::store_path(config_filepath.as_path(), config_file.clone())
+}
+
+/// Stores appconfig as its profile in the config file, leaving the other profiles as they are.
+/// Overridden settings keep the value they had in the file.
+pub fn store_appconfig(appconfig: &AppConfig) -> Result<(), Box<dyn Error>> {
+    let mut config_file = load_config_file()?;
+    let stored = config_file.profile(&appconfig.profile_name)
+        .or_else(|| config_file.profile(DEFAULT_PROFILE_NAME))
+        .unwrap_or_default();
+    let mut appconfig = appconfig.clone();
+    config_overrides().restore(&mut appconfig, &stored);
+    config_file.set_profile(appconfig);
+    Ok(store_config_file(&config_file)?)
+}
+
+/// Loads the profile chosen by the overrides, or the active profile (the default profile if the active one is missing).
+pub fn load_appconfig() -> Result<AppConfig, ConfigError> {
+    load_appconfig_profile(config_overrides().profile.as_deref())
+}
+
+/// Loads the named profile, or the active one if profile_name is None, with the overrides applied.
+pub fn load_appconfig_profile(profile_name: Option<&str>) -> Result<AppConfig, ConfigError> {
+    let config_file = load_config_file()?;
+    let mut appconfig = match profile_name {
+        Some(profile_name) => config_file.profile(profile_name)
+            .ok_or_else(|| ConfigError(format!("No profile named {} in {}", profile_name, get_config_filepath().display())))?,
+        None => config_file.profile(&config_file.active_profile)
+            .or_else(|| config_file.profile(DEFAULT_PROFILE_NAME))
+            .unwrap_or_default(),
+    };
+    config_overrides().apply(&mut appconfig);
+    Ok(appconfig)
+}
+
+/// # ConfigFile
+/// The config file: the default profile's settings at the top level, and any other named profiles under `[profiles.<name>]`,
+/// e.g. for a modded or Early Access install with its own data folder and item files.
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ConfigFile {
+    /// profile the editor starts with
+    pub active_profile: String,
+    #[serde(flatten)]
+    pub default_profile: AppConfig,
+    pub profiles: BTreeMap<String, AppConfig>,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self {
+            active_profile: DEFAULT_PROFILE_NAME.to_string(),
+            default_profile: AppConfig::default(),
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl ConfigFile {
+
+    /// The default profile first, then the others by name.
+    pub fn profile_names(&self) -> Vec<String> {
+        std::iter::once(DEFAULT_PROFILE_NAME.to_string())
+            .chain(self.profiles.keys().filter(|x| x.as_str() != DEFAULT_PROFILE_NAME).cloned())
+            .collect()
+    }
+
+    pub fn profile(&self, profile_name: &str) -> Option<AppConfig> {
+        let appconfig = match profile_name {
+            DEFAULT_PROFILE_NAME => &self.default_profile,
+            _ => self.profiles.get(profile_name)?,
+        };
+        Some(AppConfig { profile_name: profile_name.to_string(), ..appconfig.clone() })
+    }
+
+    /// Adds or replaces the profile named by appconfig.profile_name.
+    pub fn set_profile(&mut self, appconfig: AppConfig) {
+        match appconfig.profile_name.as_str() {
+            DEFAULT_PROFILE_NAME => self.default_profile = appconfig,
+            _ => {self.profiles.insert(appconfig.profile_name.clone(), appconfig);},
+        };
+    }
+
+    /// Removes a named profile, the default profile can't be removed.
+    pub fn remove_profile(&mut self, profile_name: &str) {
+        self.profiles.remove(profile_name);
+        if self.active_profile == profile_name {
+            self.active_profile = DEFAULT_PROFILE_NAME.to_string();
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
+    /// profile these settings were loaded from, see ConfigFile
+    #[serde(skip)]
+    pub profile_name: String,
     pub path_kynseed_data: String,
     pub path_kynseed_saves: String,
     pub path_saveedit_data: String,
@@ -21,12 +265,23 @@ pub struct AppConfig {
     pub filename_saveedit_name_item_lookup: String,
     pub filename_saveedit_liquid_items: String,
     pub filename_saveedit_pickup_types: String,
+    pub filename_saveedit_ailment_lookup: String,
+    pub filename_saveedit_tool_levels: String,
+    pub filename_saveedit_stats_info: String,
+    pub filename_saveedit_calendar: String,
+    pub filename_saveedit_npc_lookup: String,
+    pub filename_saveedit_relationship_fields: String,
+    pub filename_saveedit_farm: String,
+    pub filename_saveedit_known_recipes: String,
     pub b_use_embedded_saveedit_data: bool,
+    pub cure_quality_guard: CureQualityGuard,
+    pub bookmarks: Vec<SaveBookmark>,
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            profile_name: DEFAULT_PROFILE_NAME.to_string(),
             path_kynseed_data: String::from("./Data/"),
             path_kynseed_saves: String::from("./Saves/"),
             path_saveedit_data: String::from("./saveedit_data/"),
@@ -38,7 +293,93 @@ impl Default for AppConfig {
             filename_saveedit_name_item_lookup: String::from("ItemLookup.txt"),
             filename_saveedit_liquid_items: String::from("LiquidItems.txt"),
             filename_saveedit_pickup_types: String::from("PickupType.txt"),
+            filename_saveedit_ailment_lookup: String::from("AilmentLookup.txt"),
+            filename_saveedit_tool_levels: String::from("ToolLevels.txt"),
+            filename_saveedit_stats_info: String::from("StatsInfo.txt"),
+            filename_saveedit_calendar: String::from("Calendar.txt"),
+            filename_saveedit_npc_lookup: String::from("NpcLookup.txt"),
+            filename_saveedit_relationship_fields: String::from("RelationshipFields.txt"),
+            filename_saveedit_farm: String::from("Farm.txt"),
+            filename_saveedit_known_recipes: String::from("KnownRecipes.txt"),
             b_use_embedded_saveedit_data: true,
+            cure_quality_guard: CureQualityGuard::Warn,
+            bookmarks: Vec::new(),
         }
     }
 }
+
+/// What to do when a quantity is entered by hand for an item that isn't a full cure
+/// (partial cure, failure, placebo or side effect).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CureQualityGuard {
+    Off,
+    Warn,
+    Clamp,
+}
+
+/// A save tree node shown in the Quick edits window, 
+/// path is a save query (see savequery) resolved each time a save is loaded, so it works across saves and slots.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveBookmark {
+    pub label: String,
+    pub path: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides() -> ConfigOverrides {
+        ConfigOverrides {
+            path_kynseed_saves: Some("D:/Saves".to_string()),
+            filename_kynseed_save: Some("Slot2_Autosave.xml".to_string()),
+            b_use_embedded_saveedit_data: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn apply_sets_only_overridden_settings() {
+        let mut appconfig = AppConfig::default();
+        overrides().apply(&mut appconfig);
+        assert_eq!(appconfig.path_kynseed_saves, "D:/Saves");
+        assert_eq!(appconfig.filename_kynseed_save, "Slot2_Autosave.xml");
+        assert!(!appconfig.b_use_embedded_saveedit_data);
+        assert_eq!(appconfig.path_kynseed_data, AppConfig::default().path_kynseed_data);
+    }
+
+    #[test]
+    fn restore_puts_back_stored_values_of_overridden_settings() {
+        let stored = AppConfig { path_kynseed_saves: "E:/Saves".to_string(), path_kynseed_data: "E:/Data".to_string(), ..Default::default() };
+        let mut appconfig = stored.clone();
+        overrides().apply(&mut appconfig);
+        // a setting changed in the editor that isn't overridden is kept
+        appconfig.path_kynseed_data = "F:/Data".to_string();
+        overrides().restore(&mut appconfig, &stored);
+        assert_eq!(appconfig.path_kynseed_saves, "E:/Saves");
+        assert_eq!(appconfig.filename_kynseed_save, stored.filename_kynseed_save);
+        assert_eq!(appconfig.b_use_embedded_saveedit_data, stored.b_use_embedded_saveedit_data);
+        assert_eq!(appconfig.path_kynseed_data, "F:/Data");
+    }
+
+    #[test]
+    fn or_prefers_self_and_fills_from_fallback() {
+        let cli = ConfigOverrides { profile: Some("modded".to_string()), b_use_embedded_saveedit_data: Some(true), ..Default::default() };
+        let combined = cli.or(overrides());
+        assert_eq!(combined.profile.as_deref(), Some("modded"));
+        assert_eq!(combined.b_use_embedded_saveedit_data, Some(true));
+        assert_eq!(combined.path_kynseed_saves.as_deref(), Some("D:/Saves"));
+        assert!(combined.config_filepath.is_none());
+    }
+
+    #[test]
+    fn parse_bool_words() {
+        for value in ["true", "Yes", " on ", "1"] {
+            assert!(parse_bool("test", value).unwrap(), "{:?}", value);
+        };
+        for value in ["false", "NO", "off", "0"] {
+            assert!(!parse_bool("test", value).unwrap(), "{:?}", value);
+        };
+        assert!(parse_bool("test", "maybe").is_err());
+    }
+}
@@ -52,7 +52,37 @@ impl Hash for LootItem {
 pub struct HasStarRatingCondition {
     condition_type: String,
     compare_item: String,
-    has_star_rating: i32
+    has_star_rating: i32,
+    #[serde(skip)]
+    line_number: usize,
+    /// compare_item resolved to a pickup type or uid at load, for typeOfPickup, ItemLookup and UniqueID conditions
+    #[serde(skip)]
+    compare_value: Option<i32>
+}
+
+/// Where an item's has star rating result came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StarRatingSource {
+    /// First matching line of HasStarRatingConditions.txt
+    Condition {condition_type: String, compare_item: String, line_number: usize},
+    /// No condition matched
+    Default,
+}
+
+impl std::fmt::Display for StarRatingSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Condition { condition_type, compare_item, line_number } => 
+                write!(f, "line {}: {}|{}", line_number, condition_type, compare_item),
+            Self::Default => write!(f, "default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarRatingExplanation {
+    pub has_star_rating: bool,
+    pub source: StarRatingSource,
 }
 
 /// # LootManager
@@ -65,84 +95,148 @@ pub struct LootManager {
     pub pickup_type_lookup_rev: HashMap<i32, String>,
     pub liquid_item_lookup: HashSet<String>,
     pub hide_quantity_item_lookup: HashSet<String>,
-    pub has_star_rating_conditions: Vec<HasStarRatingCondition>
+    pub has_star_rating_conditions: Vec<HasStarRatingCondition>,
+    pub liquid_item_uids: HashSet<i32>,
+    pub hide_quantity_item_uids: HashSet<i32>,
+    pub star_rating_lookup: HashMap<i32, StarRatingExplanation>,
+    pub max_item_quantity_lookup: HashMap<i32, [i32; 5]>,
+    /// Names in the saveedit data that couldn't be resolved, found by the last load_data
+    pub load_warnings: Vec<String>,
 }
 
 impl LootManager {
 
     pub fn get_max_item_quantity(&self, uid: i32) -> [i32; 5] {
-        let lootitem: &LootItem = &self.full_item_lookup[&uid];
+        match self.max_item_quantity_lookup.get(&uid) {
+            Some(max_qty) => *max_qty,
+            None => [999,999,999,999,999],
+        }
+    }
 
-        let li_pickup_type_name = &self.pickup_type_lookup_rev[&lootitem.type_of_pickup];
-        let li_has_star_rating = self.has_star_rating(uid);
-        let li_is_hide_quantity_item = self.is_hide_quantity_item(uid);
+    pub fn is_liquid_item(&self, uid: i32) -> bool {
+        self.liquid_item_uids.contains(&uid)
+    }
 
-        if (li_has_star_rating) && !(li_is_hide_quantity_item) {
-            return [999,999,999,999,999];
-        };
+    pub fn is_hide_quantity_item(&self, uid: i32) -> bool {
+        self.hide_quantity_item_uids.contains(&uid)
+    }
 
-        if (!li_has_star_rating) && !(li_is_hide_quantity_item) 
-          && (["SEED", "GIFT", "VEG", "USABLE_ITEM", "EGG", "OTHER"].map(|x| x.to_string()).contains(li_pickup_type_name)) {
-            return [999,0,0,0,0];
-        };
-        [1,0,0,0,0]
+    pub fn has_star_rating(&self, uid: i32) -> bool {
+        self.explain_star_rating(uid).has_star_rating
     }
 
-    pub fn is_liquid_item(&self, uid: i32) -> bool {
-        for luid in self.liquid_item_lookup.iter().map(|x| self.name_item_lookup[x]) {
-            if luid == uid {
-                return true;
-            }
+    /// Same as has_star_rating, but also returns the condition that decided it.
+    pub fn explain_star_rating(&self, uid: i32) -> StarRatingExplanation {
+        match self.star_rating_lookup.get(&uid) {
+            Some(x) => x.clone(),
+            None => StarRatingExplanation { has_star_rating: true, source: StarRatingSource::Default },
         }
-        false
     }
 
-    pub fn is_hide_quantity_item(&self, uid: i32) -> bool {
-        for huid in self.hide_quantity_item_lookup.iter().map(|x| self.name_item_lookup[x]) {
-            if huid == uid {
-                return true;
-            }
-        }
-        false
+    /// uids of all items whose has star rating result came from the default, sorted.
+    pub fn get_default_star_rating_uids(&self) -> Vec<i32> {
+        let mut uids: Vec<i32> = self.star_rating_lookup.iter()
+            .filter_map(|(uid, x)| (x.source == StarRatingSource::Default).then_some(*uid))
+            .collect();
+        uids.sort();
+        uids
     }
 
-    pub fn has_star_rating(&self, uid: i32) -> bool {
-        let lootitem: &LootItem = &self.full_item_lookup[&uid];
+    /// Uids of the items with the SEED pickup type, sorted by name.
+    pub fn get_seed_uids(&self) -> Vec<i32> {
+        let Some(seed_type) = self.pickup_type_lookup.get("SEED") else {return Vec::new()};
+        let mut seeds: Vec<&LootItem> = self.full_item_lookup.values()
+            .filter(|x| x.type_of_pickup == *seed_type)
+            .collect();
+        seeds.sort_by(|a, b| a.name.cmp(&b.name).then(a.uid.cmp(&b.uid)));
+        seeds.into_iter().map(|x| x.uid).collect()
+    }
 
-        for condition in self.has_star_rating_conditions.iter() {
-            let condition_has_star_rating: bool = condition.has_star_rating == 1;
-            if condition.condition_type == *"UniqueID" {
-                let c_uid: i32 = condition.compare_item.parse().unwrap_or(i32::MAX);
-                if uid == c_uid {
-                    return condition_has_star_rating;
-                }
-            };
+    /// Resolves the name based saveedit data into uid sets, then caches star rating and max quantity for every item.
+    /// Names that don't resolve are added to load_warnings and otherwise ignored.
+    pub fn build_indices(&mut self) {
+        self.liquid_item_uids.clear();
+        self.hide_quantity_item_uids.clear();
+        self.star_rating_lookup.clear();
+        self.max_item_quantity_lookup.clear();
+        self.load_warnings.clear();
+
+        for name in self.liquid_item_lookup.iter() {
+            match self.name_item_lookup.get(name) {
+                Some(uid) => {self.liquid_item_uids.insert(*uid);},
+                None => self.load_warnings.push(format!("Liquid item '{}' not in item lookup", name)),
+            }
+        };
+        for name in self.hide_quantity_item_lookup.iter() {
+            match self.name_item_lookup.get(name) {
+                Some(uid) => {self.hide_quantity_item_uids.insert(*uid);},
+                None => self.load_warnings.push(format!("Hide quantity item '{}' not in item lookup", name)),
+            }
+        };
 
-            if condition.condition_type == *"typeOfPickup" {
-                let c_pickup_type: i32 = self.pickup_type_lookup[&condition.compare_item];
-                if lootitem.type_of_pickup == c_pickup_type {
-                    return condition_has_star_rating;
-                }
+        for condition in self.has_star_rating_conditions.iter_mut() {
+            condition.compare_value = match condition.condition_type.as_str() {
+                "UniqueID" => condition.compare_item.parse().ok(),
+                "typeOfPickup" => self.pickup_type_lookup.get(&condition.compare_item).copied(),
+                "ItemLookup" => self.name_item_lookup.get(&condition.compare_item).copied(),
+                _ => None,
             };
-
-            if condition.condition_type == *"namecontains" && lootitem.name.contains(&condition.compare_item) {
-                return condition_has_star_rating;
+            let b_needs_compare_value = ["UniqueID", "typeOfPickup", "ItemLookup"].contains(&condition.condition_type.as_str());
+            if b_needs_compare_value && condition.compare_value.is_none() {
+                self.load_warnings.push(format!("Star rating condition line {} '{}|{}' could not be resolved", 
+                    condition.line_number, condition.condition_type, condition.compare_item));
             };
+        };
 
-            if condition.condition_type == *"ItemLookup" {
-                let c_uid: i32 = self.name_item_lookup[&condition.compare_item];
-                if uid == c_uid {
-                    return condition_has_star_rating;
-                }
+        let star_rating_lookup: HashMap<i32, StarRatingExplanation> = self.full_item_lookup.values()
+            .map(|lootitem| (lootitem.uid, self.compute_star_rating(lootitem)))
+            .collect();
+        self.star_rating_lookup = star_rating_lookup;
+
+        let max_item_quantity_lookup: HashMap<i32, [i32; 5]> = self.full_item_lookup.values()
+            .map(|lootitem| (lootitem.uid, self.compute_max_item_quantity(lootitem)))
+            .collect();
+        self.max_item_quantity_lookup = max_item_quantity_lookup;
+    }
+
+    fn compute_star_rating(&self, lootitem: &LootItem) -> StarRatingExplanation {
+        for condition in self.has_star_rating_conditions.iter() {
+            let b_match = match condition.condition_type.as_str() {
+                "UniqueID" | "ItemLookup" => condition.compare_value == Some(lootitem.uid),
+                "typeOfPickup" => condition.compare_value == Some(lootitem.type_of_pickup),
+                "namecontains" => lootitem.name.contains(&condition.compare_item),
+                "isLiquidItem" => self.is_liquid_item(lootitem.uid),
+                _ => false,
             };
-            
-            if condition.condition_type == *"isLiquidItem" && self.is_liquid_item(uid) {
-                return condition_has_star_rating;
+            if b_match {
+                return StarRatingExplanation {
+                    has_star_rating: condition.has_star_rating == 1,
+                    source: StarRatingSource::Condition {
+                        condition_type: condition.condition_type.clone(),
+                        compare_item: condition.compare_item.clone(),
+                        line_number: condition.line_number,
+                    },
+                };
             };
         }
-        true
+        StarRatingExplanation { has_star_rating: true, source: StarRatingSource::Default }
     }
 
+    fn compute_max_item_quantity(&self, lootitem: &LootItem) -> [i32; 5] {
+        let li_pickup_type_name = self.pickup_type_lookup_rev.get(&lootitem.type_of_pickup).map(|x| x.as_str()).unwrap_or("");
+        let li_has_star_rating = self.has_star_rating(lootitem.uid);
+        let li_is_hide_quantity_item = self.is_hide_quantity_item(lootitem.uid);
+
+        if (li_has_star_rating) && !(li_is_hide_quantity_item) {
+            return [999,999,999,999,999];
+        };
+
+        if (!li_has_star_rating) && !(li_is_hide_quantity_item) 
+          && (["SEED", "GIFT", "VEG", "USABLE_ITEM", "EGG", "OTHER"].contains(&li_pickup_type_name)) {
+            return [999,0,0,0,0];
+        };
+        [1,0,0,0,0]
+    }
 
     pub fn load_data(&mut self, appconfig: &AppConfig) -> Result<(), Box<dyn Error>> {
         
@@ -179,6 +273,8 @@ impl LootManager {
         self.load_hide_quantity_item_lookup(&filepath_hide_quantity_item_lookup)?;
         self.load_has_star_rating_conditions(&filepath_has_star_rating_conditions)?;
 
+        self.build_indices();
+
         Ok(())
     }
 
@@ -189,6 +285,11 @@ impl LootManager {
         self.liquid_item_lookup.clear();
         self.hide_quantity_item_lookup.clear();
         self.has_star_rating_conditions.clear();
+        self.liquid_item_uids.clear();
+        self.hide_quantity_item_uids.clear();
+        self.star_rating_lookup.clear();
+        self.max_item_quantity_lookup.clear();
+        self.load_warnings.clear();
     }
     
     pub fn load_full_item_lookup(&mut self, folder_string: &String, filenames: &[String]) -> Result<(), Box<dyn Error>> {
@@ -340,8 +441,9 @@ impl LootManager {
                     .has_headers(false)
                     .from_path(file_path)?;
                 self.has_star_rating_conditions.clear();
-                for result in rdr.deserialize() {
-                    let record: HasStarRatingCondition = result?;
+                for (idx, result) in rdr.deserialize().enumerate() {
+                    let mut record: HasStarRatingCondition = result?;
+                    record.line_number = idx + 1;
                     self.has_star_rating_conditions.push(record);
                 };
                 Ok(())
@@ -352,8 +454,9 @@ impl LootManager {
                     .has_headers(false)
                     .from_reader(Cursor::new(HAS_STAR_RATING_CONDITIONS_TXT));
                 self.has_star_rating_conditions.clear();
-                for result in rdr.deserialize() {
-                    let record: HasStarRatingCondition = result?;
+                for (idx, result) in rdr.deserialize().enumerate() {
+                    let mut record: HasStarRatingCondition = result?;
+                    record.line_number = idx + 1;
                     self.has_star_rating_conditions.push(record);
                 };
                 Ok(())
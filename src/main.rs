@@ -3,6 +3,14 @@ mod lootitems;
 mod savedata;
 mod app;
 mod apothrecipes;
+mod savesearch;
+mod savequery;
+mod savechanges;
+mod savesections;
+mod savemodel;
+mod refdata;
+mod savesurvey;
+mod cli;
 
 
 
@@ -12,10 +20,43 @@ fn main() -> eframe::Result<()> {
     // Log to stdout (if you run with `RUST_LOG=debug`).
     tracing_subscriber::fmt::init();
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (cli_overrides, args) = match cli::parse_options(&args) {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    };
+    let env_overrides = match config::ConfigOverrides::from_env() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    };
+    // command line options take priority over environment variables, both over the config file
+    cli_overrides.or(env_overrides).install();
+
+    if let Some(cli_result) = cli::run(&args) {
+        if let Err(e) = cli_result {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        };
+        return Ok(());
+    };
+
+    let appconfig = match config::load_appconfig() {
+        Ok(x) => x,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    };
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Kynseed Save Editor",
         native_options,
-        Box::new(|cc| Ok(Box::new(app::App::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::App::new(cc, appconfig)))),
     )
 }
@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::error::Error;
+use std::str::FromStr;
+use strum::EnumString;
+
+use crate::config::AppConfig;
+use crate::savequery::SaveQuery;
+
+const TOOL_LEVELS_TXT: &str = include_str!("../saveedit_data/ToolLevels.txt");
+const STATS_INFO_TXT: &str = include_str!("../saveedit_data/StatsInfo.txt");
+const CALENDAR_TXT: &str = include_str!("../saveedit_data/Calendar.txt");
+const NPC_LOOKUP_TXT: &str = include_str!("../saveedit_data/NpcLookup.txt");
+const RELATIONSHIP_FIELDS_TXT: &str = include_str!("../saveedit_data/RelationshipFields.txt");
+const FARM_TXT: &str = include_str!("../saveedit_data/Farm.txt");
+const KNOWN_RECIPES_TXT: &str = include_str!("../saveedit_data/KnownRecipes.txt");
+
+
+#[derive(Debug, Clone)]
+pub struct RefDataError(pub String);
+
+impl std::fmt::Display for RefDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for RefDataError {}
+
+/// Reader for a pipe separated table, from file_path if it's a file, otherwise from the embedded copy.
+fn table_reader(file_path: &PathBuf, embedded: &'static str) -> Result<csv::Reader<Box<dyn std::io::Read>>, Box<dyn Error>> {
+    let reader: Box<dyn std::io::Read> = match file_path.is_file() {
+        true => Box::new(std::fs::File::open(file_path)?),
+        false => Box::new(Cursor::new(embedded)),
+    };
+    Ok(csv::ReaderBuilder::new()
+        .delimiter(b'|')
+        .has_headers(false)
+        .from_reader(reader))
+}
+
+/// Path a table is read from: the file in path_saveedit_data, unless the embedded copy is used and has rows.
+/// The embedded tables ship empty, so the file is still read while the embedded copy is empty.
+fn table_path(appconfig: &AppConfig, filename: &str, embedded: &'static str) -> PathBuf {
+    match appconfig.b_use_embedded_saveedit_data && !embedded.trim().is_empty() {
+        true => PathBuf::from("fake_path"),
+        false => PathBuf::from_iter([appconfig.path_saveedit_data.as_str(), filename]),
+    }
+}
+
+/// Note for a window whose table has no rows, saying where the file goes.
+pub fn empty_table_note(appconfig: &AppConfig, table: &str, filename: &str) -> String {
+    format!("The {} table is empty, none ships with the editor as the game's values haven't been checked. \
+        Add its lines to {} (see README) and press Reload in File->Options.",
+        table, PathBuf::from_iter([appconfig.path_saveedit_data.as_str(), filename]).display())
+}
+
+/// Reads `Name|DisplayName|Min|Max|Description` rows.
+fn read_field_infos(rdr: &mut csv::Reader<Box<dyn std::io::Read>>) -> Result<HashMap<String, FieldInfo>, Box<dyn Error>> {
+    let mut field_infos: HashMap<String, FieldInfo> = HashMap::new();
+    for result in rdr.deserialize() {
+        let record: (String, String, f64, f64, String) = result?;
+        if record.2 > record.3 {
+            return Err(Box::new(RefDataError(format!("{} has min {} above max {}", record.0, record.2, record.3))));
+        }
+        field_infos.insert(record.0, FieldInfo { display_name: record.1, min: record.2, max: record.3, description: record.4 });
+    }
+    Ok(field_infos)
+}
+
+/// Value of a row in a `Setting|Value` table, e.g. the calendar.
+fn parse_setting<T: FromStr>(table: &str, setting: &str, value: &str) -> Result<T, Box<dyn Error>> {
+    value.trim().parse::<T>().map_err(|_| Box::new(RefDataError(format!("{} setting {} {:?} is not valid", table, setting, value))) as Box<dyn Error>)
+}
+
+/// Value of a path setting, which must be an exact save query (no "//" or "*" steps) so it can't match the wrong value.
+fn parse_exact_path(table: &str, setting: &str, value: &str) -> Result<String, Box<dyn Error>> {
+    let query = SaveQuery::parse(value).map_err(|e| RefDataError(format!("{} setting {} {:?}: {}", table, setting, value, e)))?;
+    if !query.is_exact() {
+        return Err(Box::new(RefDataError(format!("{} setting {} {:?} must be an exact path, without \"//\" or \"*\"", table, setting, value))));
+    }
+    Ok(value.trim().to_string())
+}
+
+/// Value of a list setting, separated by ';'.
+fn parse_list(value: &str) -> Vec<String> {
+    value.split(';').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect()
+}
+
+/// XP thresholds for one tool type.
+/// `ExactCurrentXP` is the tool's total XP, and a tool is at the highest level whose threshold it has reached.
+#[derive(Debug, Clone, Default)]
+pub struct ToolXpTable {
+    /// (level, total XP needed to reach it), sorted by level
+    thresholds: Vec<(u8, f32)>,
+}
+
+impl ToolXpTable {
+    pub fn min_level(&self) -> u8 {
+        self.thresholds.first().map_or(0, |t| t.0)
+    }
+
+    pub fn max_level(&self) -> u8 {
+        self.thresholds.last().map_or(0, |t| t.0)
+    }
+
+    /// Total XP needed to reach the level, None if the level isn't in the table.
+    pub fn threshold(&self, level: u8) -> Option<f32> {
+        self.thresholds.iter().find(|t| t.0 == level).map(|t| t.1)
+    }
+
+    pub fn level_for_xp(&self, xp: f32) -> u8 {
+        self.thresholds.iter()
+            .take_while(|t| xp >= t.1)
+            .last()
+            .map_or(self.min_level(), |t| t.0)
+    }
+
+    /// Moves the XP into the range of the level, leaving it alone if it's already there.
+    pub fn clamp_xp_to_level(&self, level: u8, xp: f32) -> f32 {
+        let level = level.clamp(self.min_level(), self.max_level());
+        let lower = self.threshold(level).unwrap_or(0.0);
+        match self.threshold(level + 1) {
+            Some(upper) if xp >= upper => lower,
+            _ if xp < lower => lower,
+            _ => xp,
+        }
+    }
+
+    pub fn is_consistent(&self, level: u8, xp: f32) -> bool {
+        level >= self.min_level() && level <= self.max_level() && self.level_for_xp(xp) == level
+    }
+
+    /// XP gained within the level and the XP the level spans, None at max level.
+    pub fn progress(&self, level: u8, xp: f32) -> Option<(f32, f32)> {
+        let lower = self.threshold(level)?;
+        let upper = self.threshold(level + 1)?;
+        Some(((xp - lower).clamp(0.0, upper - lower), upper - lower))
+    }
+}
+
+/// Display name, range and description of a number in the save, e.g. a characterStats value.
+#[derive(Debug, Clone, Default)]
+pub struct FieldInfo {
+    /// empty to show the element name
+    pub display_name: String,
+    pub min: f64,
+    pub max: f64,
+    pub description: String,
+}
+
+/// Day, season and year as the game shows them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GameDate {
+    pub day: u32,
+    /// index into CalendarInfo::season_names
+    pub season: usize,
+    pub year: i64,
+}
+
+/// # CalendarInfo
+/// Length of the game's seasons and years, and where the save keeps the date and the character's age.
+/// Each path is an exact save query, a value whose path isn't set isn't shown.
+/// Moving the date by days, and keeping the total days counter with it, needs the days per season and the season names.
+#[derive(Debug, Clone)]
+pub struct CalendarInfo {
+    pub days_per_season: Option<u32>,
+    pub season_names: Vec<String>,
+    pub first_day: u32,
+    pub first_year: i64,
+    /// number the save uses for the first season, when it stores seasons as numbers
+    pub season_index_base: i64,
+    /// value of the total days counter on the first day of the first year
+    pub total_days_base: i64,
+    /// character age gained per year
+    pub age_per_year: f64,
+    pub day_path: Option<String>,
+    pub season_path: Option<String>,
+    pub year_path: Option<String>,
+    pub total_days_path: Option<String>,
+    pub age_path: Option<String>,
+}
+
+impl Default for CalendarInfo {
+    fn default() -> Self {
+        Self {
+            days_per_season: None,
+            season_names: Vec::new(),
+            first_day: 1,
+            first_year: 1,
+            season_index_base: 0,
+            total_days_base: 0,
+            age_per_year: 1.0,
+            day_path: None,
+            season_path: None,
+            year_path: None,
+            total_days_path: None,
+            age_path: None,
+        }
+    }
+}
+
+impl CalendarInfo {
+
+    /// None unless the days per season and the season names are set.
+    pub fn days_per_year(&self) -> Option<i64> {
+        match self.season_names.len() {
+            0 => None,
+            season_count => Some(self.days_per_season? as i64 * season_count as i64),
+        }
+    }
+
+    /// Days since the first day of the first year.
+    pub fn day_number(&self, date: &GameDate) -> Option<i64> {
+        Some((date.year - self.first_year) * self.days_per_year()?
+            + date.season as i64 * self.days_per_season? as i64
+            + date.day as i64 - self.first_day as i64)
+    }
+
+    /// Date of a day number, clamped to the first day of the first year.
+    pub fn date_from_day_number(&self, day_number: i64) -> Option<GameDate> {
+        let days_per_year = self.days_per_year()?;
+        let days_per_season = self.days_per_season? as i64;
+        let day_number = day_number.max(0);
+        let day_of_year = day_number % days_per_year;
+        Some(GameDate {
+            day: self.first_day + (day_of_year % days_per_season) as u32,
+            season: (day_of_year / days_per_season) as usize,
+            year: self.first_year + day_number / days_per_year,
+        })
+    }
+
+    /// True if no calendar settings were read.
+    pub fn is_empty(&self) -> bool {
+        self.days_per_season.is_none() && self.season_names.is_empty()
+            && [&self.day_path, &self.season_path, &self.year_path, &self.total_days_path, &self.age_path].iter().all(|x| x.is_none())
+    }
+
+    pub fn season_name(&self, season: usize) -> String {
+        match self.season_names.get(season) {
+            Some(name) => name.clone(),
+            None => format!("Season {}", season as i64 + self.season_index_base),
+        }
+    }
+
+    pub fn format_date(&self, date: &GameDate) -> String {
+        format!("Day {} of {}, year {}", date.day, self.season_name(date.season), date.year)
+    }
+}
+
+/// # FarmInfo
+/// Where the save keeps the lists of farm plots, and which of a plot's values are its crop, growth, water and quality.
+/// Each plot path is an exact save query matching one list, its children are the plots. Fields are the exact element
+/// names of a plot's children, a field that isn't set isn't shown.
+#[derive(Debug, Clone, Default)]
+pub struct FarmInfo {
+    pub plot_paths: Vec<String>,
+    pub crop_field: Option<String>,
+    pub growth_field: Option<String>,
+    pub water_field: Option<String>,
+    pub quality_field: Option<String>,
+    /// crop values of a plot with nothing planted
+    pub empty_crop_values: Vec<i32>,
+    /// growth value of a crop just planted, needed to change crops or grow them
+    pub growth_stage_base: Option<i64>,
+    /// water value of a watered plot, needed to water plots when the save stores water as a number
+    pub water_full: Option<f64>,
+}
+
+impl FarmInfo {
+
+    /// True if no farm settings were read.
+    pub fn is_empty(&self) -> bool {
+        self.plot_paths.is_empty() && self.empty_crop_values.is_empty() && self.growth_stage_base.is_none() && self.water_full.is_none()
+            && [&self.crop_field, &self.growth_field, &self.water_field, &self.quality_field].iter().all(|x| x.is_none())
+    }
+}
+
+/// Which value of an apothecary recipe the save uses to mark it known.
+#[derive(Debug, Clone, Copy, PartialEq, EnumString)]
+pub enum ApothRecipeKey {
+    ItemID,
+    AilmentID,
+    /// position in ApothRecipes.xml, from 0
+    Index,
+}
+
+/// Game data listing the entries of a known list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnownListSource {
+    /// the recipes in ApothRecipes.xml
+    ApothRecipes(ApothRecipeKey),
+    /// the items with this pickup type, by uid
+    Items(String),
+}
+
+/// # KnownListInfo
+/// A list in the save of the ids of the recipes or discoveries the player knows, and the game data listing all of them.
+#[derive(Debug, Clone)]
+pub struct KnownListInfo {
+    pub name: String,
+    pub source: KnownListSource,
+    /// exact save path of the list, the list is only edited if it matches a single element
+    pub path: String,
+}
+
+/// Reference tables from saveedit_data that describe the save rather than the game's own data.
+#[derive(Debug, Default)]
+pub struct RefDataManager {
+    pub tool_xp_tables: HashMap<String, ToolXpTable>,
+    pub stat_infos: HashMap<String, FieldInfo>,
+    pub calendar: CalendarInfo,
+    /// NPC id to name
+    pub npc_lookup: HashMap<String, String>,
+    pub relationship_field_infos: HashMap<String, FieldInfo>,
+    pub farm: FarmInfo,
+    pub known_lists: Vec<KnownListInfo>,
+}
+
+impl RefDataManager {
+    pub fn clear_data(&mut self) {
+        self.tool_xp_tables.clear();
+        self.stat_infos.clear();
+        self.calendar = CalendarInfo::default();
+        self.npc_lookup.clear();
+        self.relationship_field_infos.clear();
+        self.farm = FarmInfo::default();
+        self.known_lists.clear();
+    }
+
+    pub fn load_data(&mut self, appconfig: &AppConfig) -> Result<(), Box<dyn Error>> {
+        self.clear_data();
+
+        let filepath_tool_levels = table_path(appconfig, &appconfig.filename_saveedit_tool_levels, TOOL_LEVELS_TXT);
+        let filepath_stats_info = table_path(appconfig, &appconfig.filename_saveedit_stats_info, STATS_INFO_TXT);
+        let filepath_calendar = table_path(appconfig, &appconfig.filename_saveedit_calendar, CALENDAR_TXT);
+        let filepath_npc_lookup = table_path(appconfig, &appconfig.filename_saveedit_npc_lookup, NPC_LOOKUP_TXT);
+        let filepath_relationship_fields = table_path(appconfig, &appconfig.filename_saveedit_relationship_fields, RELATIONSHIP_FIELDS_TXT);
+        let filepath_farm = table_path(appconfig, &appconfig.filename_saveedit_farm, FARM_TXT);
+        let filepath_known_recipes = table_path(appconfig, &appconfig.filename_saveedit_known_recipes, KNOWN_RECIPES_TXT);
+        self.load_tool_levels(&filepath_tool_levels)?;
+        self.load_stats_info(&filepath_stats_info)?;
+        self.load_calendar(&filepath_calendar)?;
+        self.load_npc_lookup(&filepath_npc_lookup)?;
+        self.load_relationship_fields(&filepath_relationship_fields)?;
+        self.load_farm(&filepath_farm)?;
+        self.load_known_recipes(&filepath_known_recipes)?;
+
+        Ok(())
+    }
+
+    /// NPC name for the id, falling back to the id itself.
+    pub fn get_npc_name(&self, npc_id: &str) -> String {
+        match self.npc_lookup.get(npc_id) {
+            Some(name) => name.clone(),
+            None => npc_id.to_string(),
+        }
+    }
+
+    /// Info for a value in a relationship entry, None if the relationship fields table doesn't list it.
+    pub fn get_relationship_field_info(&self, field_name: &str) -> Option<&FieldInfo> {
+        self.relationship_field_infos.get(field_name)
+    }
+
+    /// Thresholds for the tool type, None if the tool levels table doesn't list it.
+    pub fn get_tool_xp_table(&self, tool_type: &str) -> Option<&ToolXpTable> {
+        self.tool_xp_tables.get(tool_type)
+    }
+
+    /// Info for the stat, None if the stats table doesn't list it.
+    pub fn get_stat_info(&self, stat_name: &str) -> Option<&FieldInfo> {
+        self.stat_infos.get(stat_name)
+    }
+
+    /// Reads `ToolType|Level|XP` rows, where XP is the total needed to reach Level.
+    pub fn load_tool_levels(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, TOOL_LEVELS_TXT)?;
+
+        self.tool_xp_tables.clear();
+        for result in rdr.deserialize() {
+            let record: (String, u8, f32) = result?;
+            self.tool_xp_tables.entry(record.0).or_default().thresholds.push((record.1, record.2));
+        }
+
+        for (tool_type, table) in self.tool_xp_tables.iter_mut() {
+            table.thresholds.sort_by_key(|t| t.0);
+            for pair in table.thresholds.windows(2) {
+                if pair[1].0 != pair[0].0 + 1 {
+                    return Err(Box::new(RefDataError(format!("tool levels for {} skip from level {} to {}", tool_type, pair[0].0, pair[1].0))));
+                }
+                if pair[1].1 <= pair[0].1 {
+                    return Err(Box::new(RefDataError(format!("tool levels for {} need more XP for level {} than level {}", tool_type, pair[1].0, pair[0].0))));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `Name|DisplayName|Min|Max|Description` rows, Name being the element name in characterStats.
+    pub fn load_stats_info(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, STATS_INFO_TXT)?;
+
+        self.stat_infos = read_field_infos(&mut rdr)?;
+        Ok(())
+    }
+
+    /// Reads `Name|ID` rows, ID being the NPC's id as the save's relationship entries store it.
+    pub fn load_npc_lookup(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, NPC_LOOKUP_TXT)?;
+        self.npc_lookup.clear();
+        for result in rdr.deserialize() {
+            let record: (String, String) = result?;
+            self.npc_lookup.insert(record.1, record.0);
+        }
+        Ok(())
+    }
+
+    /// Reads `Name|Source|Key|Path` rows, Source being ApothRecipes (Key ItemID, AilmentID or Index)
+    /// or Items (Key a pickup type), and Path an exact save path.
+    pub fn load_known_recipes(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, KNOWN_RECIPES_TXT)?;
+        self.known_lists.clear();
+        for result in rdr.deserialize() {
+            let record: (String, String, String, String) = result?;
+            let source = match record.1.as_str() {
+                "ApothRecipes" => KnownListSource::ApothRecipes(ApothRecipeKey::from_str(&record.2)
+                    .map_err(|_| RefDataError(format!("{} has unknown apothecary recipe key {}", record.0, record.2)))?),
+                "Items" => KnownListSource::Items(record.2),
+                _ => return Err(Box::new(RefDataError(format!("{} has unknown source {}", record.0, record.1)))),
+            };
+            let path = parse_exact_path("known recipes", &record.0, &record.3)?;
+            self.known_lists.push(KnownListInfo { name: record.0, source, path });
+        }
+        Ok(())
+    }
+
+    /// Reads `Name|DisplayName|Min|Max|Description` rows, Name being the element name of a value in a relationship entry.
+    pub fn load_relationship_fields(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, RELATIONSHIP_FIELDS_TXT)?;
+        self.relationship_field_infos = read_field_infos(&mut rdr)?;
+        Ok(())
+    }
+
+    /// Reads `Setting|Value` rows into the calendar, settings not in the file keep their defaults.
+    /// SeasonNames are separated by ';', each path setting is a single exact path.
+    pub fn load_calendar(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, CALENDAR_TXT)?;
+
+        let mut calendar = CalendarInfo::default();
+        for result in rdr.deserialize() {
+            let record: (String, String) = result?;
+            let (setting, value) = (record.0.as_str(), record.1.as_str());
+            match setting {
+                "DaysPerSeason" => calendar.days_per_season = Some(parse_setting("calendar", setting, value)?),
+                "SeasonNames" => calendar.season_names = parse_list(value),
+                "FirstDay" => calendar.first_day = parse_setting("calendar", setting, value)?,
+                "FirstYear" => calendar.first_year = parse_setting("calendar", setting, value)?,
+                "SeasonIndexBase" => calendar.season_index_base = parse_setting("calendar", setting, value)?,
+                "TotalDaysBase" => calendar.total_days_base = parse_setting("calendar", setting, value)?,
+                "AgePerYear" => calendar.age_per_year = parse_setting("calendar", setting, value)?,
+                "DayPath" => calendar.day_path = Some(parse_exact_path("calendar", setting, value)?),
+                "SeasonPath" => calendar.season_path = Some(parse_exact_path("calendar", setting, value)?),
+                "YearPath" => calendar.year_path = Some(parse_exact_path("calendar", setting, value)?),
+                "TotalDaysPath" => calendar.total_days_path = Some(parse_exact_path("calendar", setting, value)?),
+                "AgePath" => calendar.age_path = Some(parse_exact_path("calendar", setting, value)?),
+                _ => return Err(Box::new(RefDataError(format!("unknown calendar setting {}", setting)))),
+            };
+        }
+        if calendar.days_per_season == Some(0) {
+            return Err(Box::new(RefDataError("calendar DaysPerSeason must be at least one day".to_string())));
+        }
+        self.calendar = calendar;
+        Ok(())
+    }
+
+    /// Reads `Setting|Value` rows into the farm info, settings not in the file keep their defaults.
+    /// Lists, i.e. the plot paths and empty crop values, are separated by ';'.
+    pub fn load_farm(&mut self, file_path: &PathBuf) -> Result<(), Box<dyn Error>> {
+        let mut rdr = table_reader(file_path, FARM_TXT)?;
+
+        let mut farm = FarmInfo::default();
+        let field_name = |value: &str| Some(value.trim().to_string()).filter(|x| !x.is_empty());
+        for result in rdr.deserialize() {
+            let record: (String, String) = result?;
+            let (setting, value) = (record.0.as_str(), record.1.as_str());
+            match setting {
+                "PlotPaths" => farm.plot_paths = parse_list(value).iter()
+                    .map(|x| parse_exact_path("farm", setting, x))
+                    .collect::<Result<Vec<String>, _>>()?,
+                "CropField" => farm.crop_field = field_name(value),
+                "GrowthField" => farm.growth_field = field_name(value),
+                "WaterField" => farm.water_field = field_name(value),
+                "QualityField" => farm.quality_field = field_name(value),
+                "EmptyCropValues" => farm.empty_crop_values = parse_list(value).iter()
+                    .map(|x| parse_setting("farm", setting, x))
+                    .collect::<Result<Vec<i32>, _>>()?,
+                "GrowthStageBase" => farm.growth_stage_base = Some(parse_setting("farm", setting, value)?),
+                "WaterFull" => farm.water_full = Some(parse_setting("farm", setting, value)?),
+                _ => return Err(Box::new(RefDataError(format!("unknown farm setting {}", setting)))),
+            };
+        }
+        self.farm = farm;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a load_* function on the table, written to a file of its own in the temp folder.
+    fn load_table<F>(name: &str, contents: &str, load_func: F) -> Result<(), Box<dyn Error>>
+        where F: FnOnce(&PathBuf) -> Result<(), Box<dyn Error>>
+    {
+        let file_path = std::env::temp_dir().join(format!("kynseed_saveeditor_refdata_test_{}.txt", name));
+        std::fs::write(&file_path, contents).unwrap();
+        let result = load_func(&file_path);
+        std::fs::remove_file(&file_path).unwrap();
+        result
+    }
+
+    fn xp_table() -> ToolXpTable {
+        ToolXpTable { thresholds: vec![(1, 0.0), (2, 100.0), (3, 250.0)] }
+    }
+
+    #[test]
+    fn tool_xp_table_levels() {
+        let table = xp_table();
+        assert_eq!((table.min_level(), table.max_level()), (1, 3));
+        assert_eq!(table.level_for_xp(0.0), 1);
+        assert_eq!(table.level_for_xp(99.9), 1);
+        assert_eq!(table.level_for_xp(100.0), 2);
+        assert_eq!(table.level_for_xp(1000.0), 3);
+        assert!(table.is_consistent(2, 150.0));
+        assert!(!table.is_consistent(2, 250.0));
+        assert!(!table.is_consistent(4, 1000.0));
+    }
+
+    #[test]
+    fn tool_xp_table_clamp_and_progress() {
+        let table = xp_table();
+        assert_eq!(table.clamp_xp_to_level(2, 150.0), 150.0);
+        assert_eq!(table.clamp_xp_to_level(2, 50.0), 100.0);
+        assert_eq!(table.clamp_xp_to_level(2, 300.0), 100.0);
+        assert_eq!(table.clamp_xp_to_level(3, 9000.0), 9000.0);
+        assert_eq!(table.progress(2, 175.0), Some((75.0, 150.0)));
+        assert_eq!(table.progress(3, 300.0), None);
+    }
+
+    #[test]
+    fn load_tool_levels_sorts_and_checks_thresholds() {
+        let mut rd = RefDataManager::default();
+        load_table("tool_levels", "Axe|2|100\nAxe|1|0\nHoe|1|0\n", |x| rd.load_tool_levels(x)).unwrap();
+        assert_eq!(rd.get_tool_xp_table("Axe").unwrap().thresholds, vec![(1, 0.0), (2, 100.0)]);
+        assert_eq!(rd.get_tool_xp_table("Hoe").unwrap().max_level(), 1);
+        assert!(rd.get_tool_xp_table("Sickle").is_none());
+
+        let error = load_table("tool_levels_skip", "Axe|1|0\nAxe|3|100\n", |x| rd.load_tool_levels(x)).unwrap_err();
+        assert!(error.to_string().contains("skip from level 1 to 3"), "{}", error);
+        let error = load_table("tool_levels_xp", "Axe|1|50\nAxe|2|50\n", |x| rd.load_tool_levels(x)).unwrap_err();
+        assert!(error.to_string().contains("need more XP"), "{}", error);
+    }
+}
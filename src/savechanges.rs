@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+use crate::lootitems::LootManager;
+use crate::savedata::SaveDataManager;
+
+/// A change made to the save's xml tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveChangeEvent {
+    /// text content of node changed
+    TextChanged(xot::Node),
+    /// an attribute of node was set or removed
+    AttributeChanged(xot::Node),
+    /// element node was renamed
+    NodeRenamed(xot::Node),
+    /// node and its descendants were added under parent
+    NodeInserted { node: xot::Node, parent: xot::Node },
+    /// node was removed from parent, node can no longer be accessed
+    NodeRemoved { node: xot::Node, parent: xot::Node },
+    /// node was moved among its siblings
+    NodeMoved(xot::Node),
+}
+
+impl SaveChangeEvent {
+
+    /// Anything other than text and attribute changes, views holding nodes may need to look them up again.
+    pub fn is_structural(&self) -> bool {
+        !matches!(self, Self::TextChanged(_) | Self::AttributeChanged(_))
+    }
+}
+
+/// # SaveChanges
+/// Queue of changes to the xml tree, edits push to it and App hands them to every SaveChangeListener once per frame.
+/// Also holds the nodes listeners keep references to, structure edits must not remove these.
+#[derive(Default)]
+pub struct SaveChanges {
+    events: Vec<SaveChangeEvent>,
+    pinned_nodes: HashSet<xot::Node>,
+}
+
+impl SaveChanges {
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.pinned_nodes.clear();
+    }
+
+    pub fn push(&mut self, event: SaveChangeEvent) {
+        self.events.push(event);
+    }
+
+    pub fn take(&mut self) -> Vec<SaveChangeEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn pin(&mut self, node: xot::Node) {
+        self.pinned_nodes.insert(node);
+    }
+
+    pub fn is_pinned(&self, node: xot::Node) -> bool {
+        self.pinned_nodes.contains(&node)
+    }
+
+    /// Node or any of its descendants is pinned.
+    pub fn is_pinned_subtree(&self, xtree: &xot::Xot, node: xot::Node) -> bool {
+        xtree.descendants(node).any(|x| self.pinned_nodes.contains(&x))
+    }
+}
+
+/// A view holding save nodes or values read from them, updated from the changes made elsewhere.
+pub trait SaveChangeListener {
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]);
+}
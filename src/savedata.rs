@@ -1,8 +1,12 @@
 use std::path::PathBuf;
 use std::error::Error;
+use std::collections::{HashMap, HashSet};
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, CureQualityGuard, SaveBookmark};
 use crate::lootitems::{LootManager, LootItem};
+use crate::apothrecipes::ApothRecipeManager;
+use crate::savequery::SaveQuery;
+use crate::savechanges::{SaveChangeEvent, SaveChangeListener, SaveChanges};
 
 #[derive(Debug, Clone)]
 struct SaveDataError;
@@ -15,6 +19,18 @@ impl std::fmt::Display for SaveDataError {
 
 impl Error for SaveDataError {}
 
+/// A value that isn't valid for the type of the nodes it was meant for.
+#[derive(Debug, Clone)]
+pub struct SaveValueError(pub String);
+
+impl std::fmt::Display for SaveValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SaveValueError {}
+
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SaveInventoryItemRef {
@@ -30,15 +46,11 @@ impl SaveInventoryItemRef {
         uid
     }
 
-    pub fn get_uid_xt(&self, xtree: &xot::Xot) -> i32 {
-        let uid = xtree.text_content_str(self.key_int_node).unwrap().parse::<i32>().unwrap();
-        uid
-    }
-
     pub fn set_uid(&self, sm: &mut SaveDataManager, new_uid: i32) -> i32 {
         let uid_text = sm.xtree.text_content_mut(self.key_int_node).unwrap();
         uid_text.set(new_uid.to_string());
         let uid_as_set = uid_text.get().parse::<i32>().unwrap();
+        sm.changes.push(SaveChangeEvent::TextChanged(self.key_int_node));
         uid_as_set
     }
 
@@ -48,12 +60,6 @@ impl SaveInventoryItemRef {
         &lm.full_item_lookup[&uid] as _
     }
 
-    pub fn get_lootitem_ref_xt<'a>(&'a self, xtree: &xot::Xot, lm: &'a LootManager) -> &LootItem {
-        let uid = self.get_uid_xt(xtree);
-        
-        &lm.full_item_lookup[&uid] as _
-    }
-
     pub fn get_counts(&self, sm: &SaveDataManager) -> [i32; 5] {
         let mut counts: [i32; 5] = [0; 5];
         for (idx, count_ref) in self.count_int_nodes.iter().enumerate() {
@@ -64,16 +70,6 @@ impl SaveInventoryItemRef {
         counts
     }
 
-    pub fn get_counts_xt(&self, xtree: &xot::Xot) -> [i32; 5] {
-        let mut counts: [i32; 5] = [0; 5];
-        for (idx, count_ref) in self.count_int_nodes.iter().enumerate() {
-            let count_text = xtree.text_content_str(*count_ref).unwrap();
-            let count_int = count_text.parse::<i32>().unwrap();
-            counts[idx] = count_int;
-        };
-        counts
-    }
-
     // pub fn get_count_at_idx(&self, idx: usize, sm: &SaveDataManager) -> i32 {
     //     let count_ref = self.count_int_nodes[idx];
     //     let count_text = sm.xtree.text_content_str(count_ref).unwrap();
@@ -81,7 +77,11 @@ impl SaveInventoryItemRef {
     //     return count_int;
     // }
 
-    pub fn set_count_at_idx(&self, idx: usize, new_count: i32, sm: &mut SaveDataManager, lm: Option<&LootManager>) -> i32 {
+    /// Sets the count, clamped to the item's max quantity.
+    /// Returns the count as set, and whether the cure quality guard was hit (item isn't a full cure and count > 0).
+    pub fn set_count_at_idx(&self, idx: usize, new_count: i32, sm: &mut SaveDataManager, lm: Option<&LootManager>,
+        arm: Option<&ApothRecipeManager>, cure_guard: CureQualityGuard) -> (i32, bool)
+    {
         let uid = self.get_uid(sm);
         let mut max_qty = 999;
         match lm {
@@ -90,18 +90,30 @@ impl SaveInventoryItemRef {
                 max_qty = lmgr.get_max_item_quantity(uid)[idx];
             }
         };
+        let mut b_cure_guard_hit = false;
+        if let Some(arm) = arm 
+            && cure_guard != CureQualityGuard::Off && new_count > 0 && !arm.all_cures.is_empty() && arm.is_not_full_cure_id(uid) 
+        {
+            b_cure_guard_hit = true;
+            if cure_guard == CureQualityGuard::Clamp {max_qty = 0};
+        };
         let clamped_new_count = new_count.clamp(0, max_qty);
         let count_ref = self.count_int_nodes[idx];
         let count_text = sm.xtree.text_content_mut(count_ref).unwrap();
         count_text.set(clamped_new_count.to_string());
         let count_as_set = count_text.get().parse::<i32>().unwrap();
-        count_as_set
+        sm.changes.push(SaveChangeEvent::TextChanged(count_ref));
+        (count_as_set, b_cure_guard_hit)
     }
 
     pub fn remove(&mut self, sm: &mut SaveDataManager, lir: LocationItemRef) -> Result<(), Box<dyn Error>> {
         let nodeclone = self.clone();
+        let parent_node = sm.xtree.parent(self.item_node);
         match sm.xtree.remove(self.item_node) {
             Ok(_) => {
+                if let Some(parent) = parent_node {
+                    sm.changes.push(SaveChangeEvent::NodeRemoved { node: nodeclone.item_node, parent });
+                };
                 match lir {
                     LocationItemRef::Inventory => {sm.save_inventory_ref.retain(|x| x.item_node != nodeclone.item_node)},
                     LocationItemRef::NewLarder => {sm.newlarder_item_ref.retain(|x| x.item_node != nodeclone.item_node)},
@@ -117,6 +129,9 @@ impl SaveInventoryItemRef {
         let nodeclone = sm.xtree.clone_node(self.item_node);
         match sm.xtree.insert_after(self.item_node, nodeclone) {
             Ok(_) => {
+                if let Some(parent) = sm.xtree.parent(nodeclone) {
+                    sm.changes.push(SaveChangeEvent::NodeInserted { node: nodeclone, parent });
+                };
                 match lir {
                     LocationItemRef::Inventory => {
                         let child = nodeclone;
@@ -162,23 +177,11 @@ impl SaveInventoryItemRef {
 }
 
 
-pub struct ToolLevelRef {
-    pub tool_node: xot::Node,
-    pub tool_type_node: xot::Node,
-    pub tool_level_node: xot::Node,
-    pub tool_current_xp_node: xot::Node,
-}
-
 pub struct SaveDataManager {
     pub xtree: xot::Xot,
     pub root: Option<xot::Node>,
     pub doc_el: Option<xot::Node>,
     pub playerdata_node: Option<xot::Node>,
-    pub brass_count_node: Option<xot::Node>,
-    pub character_stats_node: Option<xot::Node>,
-    pub stats_nodes: Vec<xot::Node>,
-    pub tool_levelling_node: Option<xot::Node>,
-    pub tool_level_ref: Vec<ToolLevelRef>,
     pub inventory_node: Option<xot::Node>,
     pub allitems_node: Option<xot::Node>,
     pub save_inventory_ref: Vec<SaveInventoryItemRef>,
@@ -188,6 +191,9 @@ pub struct SaveDataManager {
     pub savedshops_item_ref: Vec<SaveInventoryItemRef>,
 
     pub save_tree: Option<SaveNodeTree>,
+    /// nodes matching each of AppConfig bookmarks, same order
+    pub bookmark_nodes: Vec<Vec<xot::Node>>,
+    pub changes: SaveChanges,
 }
 
 pub enum LocationItemRef {
@@ -203,11 +209,6 @@ impl Default for SaveDataManager {
             root: None,
             doc_el: None,
             playerdata_node: None,
-            brass_count_node: None,
-            character_stats_node: None,
-            stats_nodes: Vec::new(),
-            tool_levelling_node: None,
-            tool_level_ref: Vec::new(),
             inventory_node: None,
             allitems_node: None,
             save_inventory_ref: Vec::new(),
@@ -217,6 +218,8 @@ impl Default for SaveDataManager {
             savedshops_item_ref: Vec::new(),
 
             save_tree: None,
+            bookmark_nodes: Vec::new(),
+            changes: SaveChanges::default(),
         }
     }
 }
@@ -227,11 +230,6 @@ impl SaveDataManager {
         self.root = None;
         self.doc_el = None;
         self.playerdata_node = None;
-        self.brass_count_node = None;
-        self.character_stats_node = None;
-        self.stats_nodes.clear();
-        self.tool_levelling_node = None;
-        self.tool_level_ref.clear();
         self.inventory_node = None;
         self.allitems_node = None;
         self.save_inventory_ref.clear();
@@ -242,6 +240,8 @@ impl SaveDataManager {
         self.xtree = xot::Xot::new();
 
         self.save_tree = None;
+        self.bookmark_nodes.clear();
+        self.changes.clear();
     }
 
     pub fn load_data(&mut self, appconfig: &AppConfig) -> Result<(), Box<dyn Error>> {
@@ -290,27 +290,6 @@ impl SaveDataManager {
             None => return Err(Box::new(SaveDataError))
         };
 
-        self.brass_count_node = match self.get_child_node_from_name(self.playerdata_node.unwrap(), "BrassCount") {
-            Some(x) => Some(x),
-            None => return Err(Box::new(SaveDataError))
-        };
-        self.character_stats_node = match self.get_child_node_from_name(self.playerdata_node.unwrap(), "characterStats") {
-            Some(x) => Some(x),
-            None => return Err(Box::new(SaveDataError))
-        };
-        for c_stats_node in self.xtree.children(self.character_stats_node.unwrap()) {
-            match self.get_name_from_node(c_stats_node) {
-                None => continue,
-                Some(child_el_name) => if !child_el_name.contains("BASE_") {continue}
-            };
-            self.stats_nodes.push(c_stats_node);
-        }
-
-        self.tool_levelling_node = match self.get_child_node_from_name(self.playerdata_node.unwrap(), "ToolLevelling") {
-            Some(x) => Some(x),
-            None => return Err(Box::new(SaveDataError))
-        };
-
         self.inventory_node = match self.get_child_node_from_name( self.playerdata_node.unwrap(), "Inventory") {
             Some(x) => Some(x),
             None => return Err(Box::new(SaveDataError))
@@ -360,9 +339,9 @@ impl SaveDataManager {
         self.load_newlarder_data()?;
         self.load_savedshops_data()?;
 
-        self.load_tool_levels()?;
-
         self.save_tree = Some(SaveNodeTree::new(&self.doc_el.unwrap(), &self.xtree));
+        self.resolve_bookmarks(&appconfig.bookmarks);
+        self.pin_tracked_nodes();
 
         Ok(())
         
@@ -380,18 +359,8 @@ impl SaveDataManager {
                 None => continue,
                 Some(d_name) => {
                     if d_name == "ItemStack" {
-                        let d_key_node = self.get_child_node_from_name(descendant, "UniqueID")
-                            .ok_or_else(|| Box::new(SaveDataError))?;
-                        let mut d_count_int_nodes:Vec<xot::Node> = Vec::new();
-                        if let Some(d_count_node) = self.get_child_node_from_name(descendant, "Count") {
-                            for maybe_int_node in self.xtree.children(d_count_node) {
-                                match self.get_name_from_node( maybe_int_node) {
-                                    None => continue,
-                                    Some(el_name) => {if el_name == "int" {d_count_int_nodes.push(maybe_int_node);}}
-                                }
-                            };
-                        };
-                        self.newlarder_item_ref.push(SaveInventoryItemRef{item_node: descendant, key_int_node: d_key_node, count_int_nodes: d_count_int_nodes });
+                        let save_item_ref = Self::get_sir_from_itemstack_node(descendant, &self.xtree)?;
+                        self.newlarder_item_ref.push(save_item_ref);
                     }
                 }
             }
@@ -411,18 +380,8 @@ impl SaveDataManager {
                 None => continue,
                 Some(d_name) => {
                     if d_name == "ItemStack" {
-                        let d_key_node = self.get_child_node_from_name(descendant, "UniqueID")
-                            .ok_or_else(|| Box::new(SaveDataError))?;
-                        let mut d_count_int_nodes:Vec<xot::Node> = Vec::new();
-                        if let Some(d_count_node) = self.get_child_node_from_name(descendant, "Count") {
-                            for maybe_int_node in self.xtree.children(d_count_node) {
-                                match self.get_name_from_node( maybe_int_node) {
-                                    None => continue,
-                                    Some(el_name) => {if el_name == "int" {d_count_int_nodes.push(maybe_int_node);}}
-                                }
-                            };
-                        };
-                        self.savedshops_item_ref.push(SaveInventoryItemRef{item_node: descendant, key_int_node: d_key_node, count_int_nodes: d_count_int_nodes });
+                        let save_item_ref = Self::get_sir_from_itemstack_node(descendant, &self.xtree)?;
+                        self.savedshops_item_ref.push(save_item_ref);
                     }
                 }
             }
@@ -431,30 +390,129 @@ impl SaveDataManager {
 
     }
 
-    pub fn load_tool_levels(&mut self) -> Result<(), Box<dyn Error>>{
-        match self.tool_levelling_node {
-            None => return Ok(()),
-            Some(_node) => {}
+    /// Sets the text of a leaf element and records the change, returns the text as set.
+    pub fn set_node_text(&mut self, node: xot::Node, text: String) -> Option<String> {
+        let node_text = self.xtree.text_content_mut(node)?;
+        node_text.set(text);
+        let text_as_set = node_text.get().to_string();
+        self.changes.push(SaveChangeEvent::TextChanged(node));
+        Some(text_as_set)
+    }
+
+    /// Nodes the editor keeps references to, see SaveChanges. Save sections pin their own.
+    pub fn pin_tracked_nodes(&mut self) {
+        let tracked_nodes: Vec<xot::Node> = [self.doc_el, self.playerdata_node, self.inventory_node, self.allitems_node, 
+            self.newlarder_node, self.savedshops_node]
+            .into_iter()
+            .flatten()
+            .collect();
+        tracked_nodes.into_iter().for_each(|x| self.changes.pin(x));
+    }
+
+    /// Takes the changes recorded since the last call, for the listeners. 
+    /// After structure changes, item refs are looked up again: removed or broken items are dropped, inserted items are added.
+    pub fn take_changes(&mut self) -> Vec<SaveChangeEvent> {
+        let events = self.changes.take();
+        if !events.iter().any(|x| x.is_structural()) {return events};
+
+        let xtree = &self.xtree;
+        let allitems_node = self.allitems_node;
+        let is_inventory_item = |node: xot::Node| !xtree.is_removed(node) && allitems_node.is_some() && xtree.parent(node) == allitems_node;
+        let is_itemstack_under = |node: xot::Node, location_node: Option<xot::Node>| {
+            !xtree.is_removed(node) 
+                && location_node.is_some_and(|location| xtree.ancestors(node).any(|x| x == location))
         };
 
-        for child in self.xtree.children(self.tool_levelling_node.unwrap()) {
-            match self.get_name_from_node(child) {
-                None => continue,
-                Some(c_name) => {
-                    if c_name == "ToolLevel" {
-                        let c_type_node = self.get_child_node_from_name(child, "type")
-                            .ok_or_else(|| Box::new(SaveDataError))?;
-                        let c_level_node = self.get_child_node_from_name(child, "Level")
-                            .ok_or_else(|| Box::new(SaveDataError))?;
-                        let c_currentxp_node = self.get_child_node_from_name(child, "ExactCurrentXP")
-                            .ok_or_else(|| Box::new(SaveDataError))?;
-                        self.tool_level_ref.push(ToolLevelRef { tool_node: child, tool_type_node: c_type_node, tool_level_node: c_level_node, tool_current_xp_node: c_currentxp_node });
-                    }
-                }
-            }
+        let resolve_inventory = |node: xot::Node| is_inventory_item(node).then(|| Self::get_sir_from_item_node(node, xtree).ok()).flatten();
+        let resolve_itemstack = |node: xot::Node, location_node: Option<xot::Node>| {
+            is_itemstack_under(node, location_node).then(|| Self::get_sir_from_itemstack_node(node, xtree).ok()).flatten()
         };
-        Ok(())
 
+        self.save_inventory_ref = self.save_inventory_ref.iter().filter_map(|x| resolve_inventory(x.item_node)).collect();
+        self.newlarder_item_ref = self.newlarder_item_ref.iter().filter_map(|x| resolve_itemstack(x.item_node, self.newlarder_node)).collect();
+        self.savedshops_item_ref = self.savedshops_item_ref.iter().filter_map(|x| resolve_itemstack(x.item_node, self.savedshops_node)).collect();
+
+        for event in events.iter() {
+            let SaveChangeEvent::NodeInserted { node, .. } = event else {continue};
+            for descendant in xtree.descendants(*node) {
+                match Self::get_name_from_node_xt(xtree, descendant) {
+                    Some("item") => {
+                        if self.save_inventory_ref.iter().any(|x| x.item_node == descendant) {continue};
+                        if let Some(x) = resolve_inventory(descendant) {self.save_inventory_ref.push(x)};
+                    },
+                    Some("ItemStack") => {
+                        if !self.newlarder_item_ref.iter().any(|x| x.item_node == descendant)
+                            && let Some(x) = resolve_itemstack(descendant, self.newlarder_node)
+                        {
+                            self.newlarder_item_ref.push(x);
+                        };
+                        if !self.savedshops_item_ref.iter().any(|x| x.item_node == descendant)
+                            && let Some(x) = resolve_itemstack(descendant, self.savedshops_node)
+                        {
+                            self.savedshops_item_ref.push(x);
+                        };
+                    },
+                    _ => {},
+                };
+            };
+        };
+
+        self.bookmark_nodes.iter_mut().for_each(|nodes| nodes.retain(|x| !xtree.is_removed(*x)));
+        events
+    }
+
+    /// Nodes matching a SaveQuery path, see savequery::SaveQuery.
+    /// Bookmarks that don't match anything in this save resolve to no nodes.
+    pub fn resolve_bookmarks(&mut self, bookmarks: &[SaveBookmark]) {
+        self.bookmark_nodes = bookmarks.iter()
+            .map(|x| self.query(&x.path).unwrap_or_default())
+            .collect();
+    }
+
+    pub fn query(&self, path: &str) -> Result<Vec<xot::Node>, Box<dyn Error>> {
+        let doc_el = self.doc_el.ok_or_else(|| Box::new(SaveDataError))?;
+        let save_query = SaveQuery::parse(path)?;
+        Ok(save_query.select(&self.xtree, doc_el))
+    }
+
+    /// (full path, text) of each node matching a SaveQuery path.
+    pub fn query_get_strs(&self, path: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let nodes = self.query(path)?;
+        Ok(Self::get_node_paths_xt(&self.xtree, &nodes).into_iter()
+            .zip(nodes.iter())
+            .map(|(node_path, x)| (node_path, self.xtree.text_content_str(*x).unwrap_or("").to_string()))
+            .collect())
+    }
+
+    /// Sets the text of every leaf element matching a SaveQuery path, returns how many were set.
+    /// Matched elements with child elements are skipped. The value is checked against each leaf's SaveValueType first,
+    /// and nothing is set if it isn't valid for all of them.
+    pub fn query_set_str(&mut self, path: &str, value: &str) -> Result<usize, Box<dyn Error>> {
+        let leaf_nodes: Vec<xot::Node> = self.query(path)?.into_iter()
+            .filter(|node| self.xtree.is_element(*node) && !self.xtree.children(*node).any(|x| self.xtree.is_element(x)))
+            .collect();
+
+        let mut texts: Vec<(xot::Node, String)> = Vec::new();
+        let mut invalid: Vec<String> = Vec::new();
+        for (node, node_path) in leaf_nodes.iter().zip(Self::get_node_paths_xt(&self.xtree, &leaf_nodes)) {
+            let value_type = SaveValueType::infer(&self.xtree, *node);
+            match value_type.validate(value.to_string()) {
+                Some(text) => texts.push((*node, text)),
+                None => invalid.push(format!("{} ({})", node_path, value_type.label())),
+            };
+        };
+        if !invalid.is_empty() {
+            return Err(Box::new(SaveValueError(format!("{:?} is not valid for {}, nothing was set", value, invalid.join(", ")))));
+        };
+
+        for (node, text) in texts.iter() {
+            match self.xtree.text_content_mut(*node) {
+                Some(node_text) => node_text.set(text),
+                None => self.xtree.append_text(*node, text)?,
+            };
+            self.changes.push(SaveChangeEvent::TextChanged(*node));
+        };
+        Ok(texts.len())
     }
 
     pub fn get_name_from_node(&self, node: xot::Node) -> Option<&str> {
@@ -517,6 +575,138 @@ impl SaveDataManager {
         None
     }
 
+    /// Document element down to node, each with its name and 1-based position among same named siblings if it has any, e.g. "item[3]".
+    pub fn get_node_path_parts_xt(xtree: &xot::Xot, node: xot::Node) -> Vec<(xot::Node, String)> {
+        let mut path_parts: Vec<(xot::Node, String)> = Vec::new();
+        for ancestor in xtree.ancestors(node) {
+            let Some(name) = Self::get_name_from_node_xt(xtree, ancestor) else {continue};
+            let mut part = name.to_string();
+            if let Some(parent) = xtree.parent(ancestor) {
+                let same_name_siblings: Vec<xot::Node> = xtree.children(parent)
+                    .filter(|x| Self::get_name_from_node_xt(xtree, *x) == Some(name))
+                    .collect();
+                if same_name_siblings.len() > 1 {
+                    let position = same_name_siblings.iter().position(|x| *x == ancestor).unwrap_or(0) + 1;
+                    part.push_str(&format!("[{}]", position));
+                };
+            };
+            path_parts.push((ancestor, part));
+        };
+        path_parts.reverse();
+        path_parts
+    }
+
+    /// Path of element names from the document element down to node, e.g. "SaveGame/PlayerData/Inventory/AllItems/item[3]".
+    pub fn get_node_path_xt(xtree: &xot::Xot, node: xot::Node) -> String {
+        Self::get_node_path_parts_xt(xtree, node).into_iter()
+            .map(|(_, part)| part)
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    /// Paths of many nodes, as get_node_path_xt gives them, working out the positions among each parent's children only once.
+    pub fn get_node_paths_xt(xtree: &xot::Xot, nodes: &[xot::Node]) -> Vec<String> {
+        let mut parts_by_parent: HashMap<xot::Node, HashMap<xot::Node, String>> = HashMap::new();
+        nodes.iter()
+            .map(|node| {
+                let mut path_parts: Vec<String> = Vec::new();
+                for ancestor in xtree.ancestors(*node) {
+                    let Some(name) = Self::get_name_from_node_xt(xtree, ancestor) else {continue};
+                    let part = match xtree.parent(ancestor) {
+                        Some(parent) => parts_by_parent.entry(parent)
+                            .or_insert_with(|| Self::get_child_path_parts_xt(xtree, parent))
+                            .get(&ancestor).cloned().unwrap_or_else(|| name.to_string()),
+                        None => name.to_string(),
+                    };
+                    path_parts.push(part);
+                };
+                path_parts.reverse();
+                path_parts.join("/")
+            })
+            .collect()
+    }
+
+    /// Path part of each child element of parent, its name and position among same named siblings if it has any.
+    fn get_child_path_parts_xt(xtree: &xot::Xot, parent: xot::Node) -> HashMap<xot::Node, String> {
+        let children: Vec<(xot::Node, &str)> = xtree.children(parent)
+            .filter_map(|x| Some((x, Self::get_name_from_node_xt(xtree, x)?)))
+            .collect();
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for (_, name) in children.iter() {
+            *name_counts.entry(name).or_default() += 1;
+        };
+        let mut name_positions: HashMap<&str, usize> = HashMap::new();
+        children.into_iter()
+            .map(|(child, name)| {
+                let part = match name_counts[name] {
+                    1 => name.to_string(),
+                    _ => {
+                        let position = name_positions.entry(name).or_default();
+                        *position += 1;
+                        format!("{}[{}]", name, position)
+                    },
+                };
+                (child, part)
+            })
+            .collect()
+    }
+
+    /// "key/int=512" for dictionary entries like inventory items, which have a key element holding a single value.
+    pub fn get_key_predicate_xt(xtree: &xot::Xot, node: xot::Node) -> Option<String> {
+        let key_node = Self::get_child_node_from_name_xt(xtree, node, "key")?;
+        let key_children: Vec<xot::Node> = xtree.children(key_node).filter(|x| xtree.is_element(*x)).collect();
+        let [key_value_node] = key_children[..] else {return None};
+        let key_value_name = Self::get_name_from_node_xt(xtree, key_value_node)?;
+        let key_value = xtree.text_content_str(key_value_node)?.trim();
+        if key_value.is_empty() || key_value.contains(['[', ']', '"', '\'', '/']) {return None};
+        Some(format!("key/{}={}", key_value_name, key_value))
+    }
+
+    /// Absolute save query path to node, like get_node_path_xt, but same named siblings are told apart by 
+    /// their key (e.g. item[key/int=512]) where possible so the path still finds the node in other saves.
+    pub fn get_node_query_path_xt(xtree: &xot::Xot, node: xot::Node) -> String {
+        let mut path_parts: Vec<String> = Vec::new();
+        for ancestor in xtree.ancestors(node) {
+            let Some(name) = Self::get_name_from_node_xt(xtree, ancestor) else {continue};
+            let mut part = name.to_string();
+            if let Some(parent) = xtree.parent(ancestor) {
+                let same_name_siblings: Vec<xot::Node> = xtree.children(parent)
+                    .filter(|x| Self::get_name_from_node_xt(xtree, *x) == Some(name))
+                    .collect();
+                if same_name_siblings.len() > 1 {
+                    let key_predicate = Self::get_key_predicate_xt(xtree, ancestor).filter(|key_predicate| {
+                        same_name_siblings.iter()
+                            .filter(|x| Self::get_key_predicate_xt(xtree, **x).as_ref() == Some(key_predicate))
+                            .count() == 1
+                    });
+                    match key_predicate {
+                        Some(key_predicate) => part.push_str(&format!("[{}]", key_predicate)),
+                        None => {
+                            let position = same_name_siblings.iter().position(|x| *x == ancestor).unwrap_or(0) + 1;
+                            part.push_str(&format!("[{}]", position));
+                        },
+                    };
+                };
+            };
+            path_parts.push(part);
+        };
+        path_parts.reverse();
+        format!("/{}", path_parts.join("/"))
+    }
+
+    /// Larder and shop ItemStack, UniqueID and Count/int children.
+    pub fn get_sir_from_itemstack_node(node: xot::Node, xtree: &xot::Xot) -> Result<SaveInventoryItemRef, Box<dyn Error>> {
+        let key_node = Self::get_child_node_from_name_xt(xtree, node, "UniqueID")
+            .ok_or_else(|| Box::new(SaveDataError))?;
+        let mut count_int_nodes: Vec<xot::Node> = Vec::new();
+        if let Some(count_node) = Self::get_child_node_from_name_xt(xtree, node, "Count") {
+            count_int_nodes = xtree.children(count_node)
+                .filter(|x| Self::get_name_from_node_xt(xtree, *x) == Some("int"))
+                .collect();
+        };
+        Ok(SaveInventoryItemRef{item_node: node, key_int_node: key_node, count_int_nodes })
+    }
+
     pub fn get_sir_from_item_node(child: xot::Node, xtree: &xot::Xot) -> Result<SaveInventoryItemRef, Box<dyn Error>> {
         let child_key_node = Self::get_child_node_from_name_xt( xtree, child, "key")
             .ok_or_else(|| Box::new(SaveDataError))?;
@@ -549,10 +739,193 @@ impl SaveDataManager {
     }
 }
 
-// SaveNodeTree(Node, Name, Text Content, b Has Text Content, Children).
+/// Value type of a save tree leaf, inferred from its element name, its value and the values of similar nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveValueType {
+    Bool,
+    /// element named after an integer type, e.g. "int"
+    Int,
+    /// whole numbers so far, but not declared as integers, so decimals are allowed
+    Number,
+    Float,
+    Guid,
+    /// Enum-like identifiers, with the values seen on similar nodes
+    Enum(Vec<String>),
+    Text,
+}
+
+impl SaveValueType {
+
+    /// Infers the type of node's text from its element name (xml serialized types such as "int"),
+    /// and from the values of node and its cousins, same named elements under same named parents,
+    /// e.g. all ToolLevelling/ToolLevel/type.
+    pub fn infer(xtree: &xot::Xot, node: xot::Node) -> Self {
+        let el_node = match xtree.is_text(node) {
+            true => match xtree.parent(node) {
+                Some(x) => x,
+                None => return Self::Text,
+            },
+            false => node,
+        };
+        let Some(el_name) = SaveDataManager::get_name_from_node_xt(xtree, el_node) else {return Self::Text};
+        if let Some(value_type) = Self::from_type_name(el_name) {return value_type};
+
+        let mut samples: Vec<String> = Vec::new();
+        if let Some(parent) = xtree.parent(el_node) {
+            let parent_name = SaveDataManager::get_name_from_node_xt(xtree, parent);
+            let parents: Vec<xot::Node> = match xtree.parent(parent) {
+                Some(grandparent) if parent_name.is_some() => xtree.children(grandparent)
+                    .filter(|x| SaveDataManager::get_name_from_node_xt(xtree, *x) == parent_name)
+                    .collect(),
+                _ => vec![parent],
+            };
+            for cousin_parent in parents {
+                for cousin in xtree.children(cousin_parent) {
+                    if SaveDataManager::get_name_from_node_xt(xtree, cousin) != Some(el_name) {continue};
+                    if xtree.children(cousin).any(|x| xtree.is_element(x)) {continue};
+                    if let Some(text) = xtree.text_content_str(cousin) && !text.trim().is_empty() {
+                        samples.push(text.trim().to_string());
+                    };
+                };
+            };
+        };
+        if samples.is_empty() {
+            match xtree.text_content_str(el_node) {
+                Some(text) if !text.trim().is_empty() => samples.push(text.trim().to_string()),
+                _ => return Self::Text,
+            };
+        };
+
+        if samples.iter().all(|x| x.eq_ignore_ascii_case("true") || x.eq_ignore_ascii_case("false")) {return Self::Bool};
+        // whole numbers could still be a float field that happens to hold whole values
+        if samples.iter().all(|x| x.parse::<i64>().is_ok()) {return Self::Number};
+        if samples.iter().all(|x| x.parse::<f64>().is_ok()) {return Self::Float};
+        if samples.iter().all(|x| is_guid(x)) {return Self::Guid};
+
+        let b_identifiers = samples.iter().all(|x| {
+            x.chars().next().is_some_and(|c| c.is_alphabetic()) && x.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+        let mut distinct_values: Vec<String> = samples.clone();
+        distinct_values.sort();
+        distinct_values.dedup();
+        if b_identifiers && (2..=32).contains(&distinct_values.len()) {
+            return Self::Enum(distinct_values);
+        };
+        Self::Text
+    }
+
+    /// Type of an element named after an xml serialized type, e.g. "int".
+    fn from_type_name(el_name: &str) -> Option<Self> {
+        match el_name {
+            "int" | "long" | "short" | "byte" => Some(Self::Int),
+            "float" | "double" | "decimal" => Some(Self::Float),
+            "boolean" | "bool" => Some(Self::Bool),
+            "guid" => Some(Self::Guid),
+            _ => None,
+        }
+    }
+
+    /// Validator for set_str_from_self_with_check, returns the value to store, or None if it isn't valid for the type.
+    pub fn validate(&self, value: String) -> Option<String> {
+        let trimmed = value.trim();
+        match self {
+            // any casing is read, but only "true"/"True" and "false"/"False" are stored
+            Self::Bool => match trimmed.to_ascii_lowercase().as_str() {
+                "true" => Some(bool_text_like(true, trimmed).to_string()),
+                "false" => Some(bool_text_like(false, trimmed).to_string()),
+                _ => None,
+            },
+            Self::Int => trimmed.parse::<i64>().ok().map(|x| x.to_string()),
+            Self::Number | Self::Float => trimmed.parse::<f64>().is_ok_and(|x| x.is_finite()).then(|| trimmed.to_string()),
+            Self::Guid => is_guid(trimmed).then(|| trimmed.to_string()),
+            Self::Enum(_) => (!trimmed.is_empty() && !trimmed.contains(char::is_whitespace)).then(|| trimmed.to_string()),
+            Self::Text => Some(value),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::Int => "int",
+            Self::Number => "number",
+            Self::Float => "float",
+            Self::Guid => "guid",
+            Self::Enum(_) => "enum",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// # SaveValueTypeCache
+/// Types from SaveValueType::infer, kept per element name under same named parents (the cousins infer samples),
+/// so the values aren't sampled again every frame. Clear it when the save changes.
+#[derive(Debug, Default)]
+pub struct SaveValueTypeCache(HashMap<(Option<xot::Node>, String, String), SaveValueType>);
+
+impl SaveValueTypeCache {
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Inferred type of node's text, see SaveValueType::infer.
+    pub fn get(&mut self, xtree: &xot::Xot, node: xot::Node) -> SaveValueType {
+        let el_node = match xtree.is_text(node) {
+            true => xtree.parent(node).unwrap_or(node),
+            false => node,
+        };
+        let el_name = SaveDataManager::get_name_from_node_xt(xtree, el_node).unwrap_or_default();
+        if let Some(value_type) = SaveValueType::from_type_name(el_name) {return value_type};
+
+        let parent = xtree.parent(el_node);
+        let parent_name = parent.and_then(|x| SaveDataManager::get_name_from_node_xt(xtree, x)).unwrap_or_default();
+        let key = (parent.and_then(|x| xtree.parent(x)), parent_name.to_string(), el_name.to_string());
+        self.0.entry(key).or_insert_with(|| SaveValueType::infer(xtree, node)).clone()
+    }
+}
+
+/// Text for a float value, written like the text it replaces: with at least as many decimals, 
+/// and otherwise the fewest that read back as the value (to 6 decimals, dropping drag steps' rounding errors).
+pub fn float_text_like(value: f64, like: &str) -> String {
+    let like_decimals = like.trim().split_once('.')
+        .map_or(0, |(_, x)| x.chars().take_while(|c| c.is_ascii_digit()).count());
+    let rounded = (value * 1e6).round() / 1e6;
+    let text = match rounded.is_finite() {
+        true => rounded.to_string(),
+        false => value.to_string(),
+    };
+    let decimals = text.split_once('.').map_or(0, |(_, x)| x.len());
+    match (decimals, like_decimals) {
+        (_, 0) => text,
+        (0, _) => format!("{}.{}", text, "0".repeat(like_decimals)),
+        _ if decimals < like_decimals => format!("{}{}", text, "0".repeat(like_decimals - decimals)),
+        _ => text,
+    }
+}
+
+/// "true" or "false" for b_value, capitalised like the text it replaces so the save keeps its own style.
+pub fn bool_text_like(b_value: bool, like: &str) -> &'static str {
+    match (b_value, like.trim().starts_with(|c: char| c.is_uppercase())) {
+        (true, true) => "True",
+        (true, false) => "true",
+        (false, true) => "False",
+        (false, false) => "false",
+    }
+}
+
+/// 8-4-4-4-12 hex digits, e.g. "3f2504e0-4f89-11d3-9a0c-0305e82c3301"
+pub fn is_guid(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() == 5
+        && parts.iter().zip([8, 4, 4, 4, 12]).all(|(part, len)| part.len() == len && part.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// SaveNodeTree(Node, Name, Text Content, b Has Text Content, Children, b Children Loaded).
 // xtree is xot::Xot which every Node references
+// Children are only built when a branch is first expanded (load_children), 
+// nodes are found by following xot's parent links from the node up to the tree root (get_tree_position).
 #[derive(Clone)]
-pub struct SaveNodeTree(pub xot::Node, pub String, pub String, pub bool, pub Vec<SaveNodeTree>);
+pub struct SaveNodeTree(pub xot::Node, pub String, pub String, pub bool, pub Vec<SaveNodeTree>, pub bool);
 
 impl SaveNodeTree {
 
@@ -568,33 +941,21 @@ impl SaveNodeTree {
             None => {text = String::default();}
         };
         let b_has_text_content = !text.trim().is_empty();
-        let children = Self::get_good_children_from_node(node, xtree);
         Self {
             0: node.clone(),
             1: name,
             2: text,
             3: b_has_text_content,
-            4: children,
+            4: Vec::new(),
+            5: false,
         }
     }
 
-    pub fn reload_data(&mut self, xtree: &xot::Xot) {
-        let name: String;
-        let text: String;
-        match Self::get_name_from_node(&self.0, xtree) {
-            Some(s) => {name = s.to_string();},
-            None => {name = String::default();}
-        };
-        match Self::get_str_from_node(&self.0, xtree) {
-            Some(t) => {text = t.to_string();},
-            None => {text = String::default();}
-        };
-        let b_has_text_content = !text.trim().is_empty();
-        let children = Self::get_good_children_from_node(&self.0, xtree);
-        self.1 = name;
-        self.2 = text;
-        self.3 = b_has_text_content;
-        self.4 = children;
+    /// Builds the children of this branch if they haven't been yet.
+    pub fn load_children(&mut self, xtree: &xot::Xot) {
+        if self.5 {return};
+        self.4 = Self::get_good_children_from_node(&self.0, xtree);
+        self.5 = true;
     }
 
     pub fn get_good_children_from_node(node: &xot::Node, xtree: &xot::Xot) -> Vec<Self> {
@@ -668,14 +1029,44 @@ impl SaveNodeTree {
         };
     }
 
-    pub fn update_all_strings(&mut self, xtree: &xot::Xot) {
-        self.update_strings_from_self(xtree);
-        self.4.iter_mut().for_each(|child| child.update_all_strings(xtree));
+    /// Updates the strings of only the changed nodes, along with their parent and children which show the same text.
+    /// Nodes in branches that haven't been loaded are skipped, they're read when loaded.
+    pub fn update_strings_for_nodes(root: &mut Self, xtree: &xot::Xot, nodes: &[xot::Node]) {
+        for node in nodes.iter() {
+            if let Some(parent_node) = xtree.parent(*node)
+                && let Some(parent) = Self::find_node_mut(root, xtree, &parent_node)
+            {
+                parent.update_strings_from_self(xtree);
+            };
+            if let Some(tree_item) = Self::find_node_mut(root, xtree, node) {
+                tree_item.update_strings_from_self(xtree);
+                tree_item.4.iter_mut().for_each(|child| child.update_strings_from_self(xtree));
+            };
+        };
+    }
+
+    /// Child indices from root down to node, following xot's parent links, 
+    /// None if node isn't below root or is in a branch that hasn't been loaded.
+    pub fn get_tree_position(root: &Self, xtree: &xot::Xot, node: &xot::Node) -> Option<Vec<usize>> {
+        let ancestors: Vec<xot::Node> = xtree.ancestors(*node).collect();
+        let root_idx = ancestors.iter().position(|x| *x == root.0)?;
+
+        let mut position: Vec<usize> = Vec::with_capacity(root_idx);
+        let mut tree_item = root;
+        for ancestor in ancestors[..root_idx].iter().rev() {
+            let child_idx = tree_item.4.iter().position(|x| x.0 == *ancestor)?;
+            position.push(child_idx);
+            tree_item = &tree_item.4[child_idx];
+        };
+        Some(position)
     }
 
-    pub fn set_str_from_self(&mut self, xtree: &mut xot::Xot) {
-        let return_str = Self::set_str_from_node(&self.0, xtree, self.2.clone());
-        if let Some(x) = return_str {self.2 = x.to_string()} else {self.2 = String::default()};
+    pub fn get_at_position_mut<'a>(root: &'a mut Self, position: &[usize]) -> Option<&'a mut Self> {
+        let mut tree_item = root;
+        for child_idx in position.iter() {
+            tree_item = tree_item.4.get_mut(*child_idx)?;
+        };
+        Some(tree_item)
     }
 
     pub fn set_str_from_self_with_check<F>(&mut self, xtree: &mut xot::Xot, check_func: F) 
@@ -697,40 +1088,25 @@ impl SaveNodeTree {
         };
     }
 
-    pub fn find_parent_childidx_from_node<'a>(root: &'a mut Self, node: &'a xot::Node) -> Result<(&'a mut Self, usize), String> {
-        let mut child_idx: Option<usize> = None;
-
+    pub fn find_parent_childidx_from_node<'a>(root: &'a mut Self, xtree: &xot::Xot, node: &xot::Node) -> Result<(&'a mut Self, usize), String> {
         if root.0 == *node {return Err("No parent, node is root.".to_string())};
-        if root.4.is_empty() {return Err("No children.".to_string())};
 
-        for (idx, child) in root.4.iter().enumerate() {
-            if child.0 == *node {
-                child_idx = Some(idx); 
-                break;
-            };
-        };
-        if child_idx.is_some() {
-            return Ok((root, child_idx.unwrap()));
-        };
-
-        for child in root.4.iter_mut() {
-            if let Ok(child_result) = Self::find_parent_childidx_from_node(child, node) {
-                return Ok(child_result);
-            } else {
-                continue;
-            }
+        let mut position = Self::get_tree_position(root, xtree, node).ok_or_else(|| "Could not find node in tree.".to_string())?;
+        let Some(child_idx) = position.pop() else {return Err("Could not find node in tree.".to_string())};
+        match Self::get_at_position_mut(root, &position) {
+            Some(parent) => Ok((parent, child_idx)),
+            None => Err("Could not find node in tree.".to_string()),
         }
-        Err("Could not find node in tree.".to_string())
     }
 
     pub fn copy_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node) -> Result<xot::Node, String> {
         let node_deref = node.clone();
         let new_node = xtree.clone_node(node_deref);
-        if let Ok((parent, child_idx)) = Self::find_parent_childidx_from_node(root, &node) {
+        if let Ok((parent, child_idx)) = Self::find_parent_childidx_from_node(root, xtree, &node) {
             match xtree.insert_after(node_deref, new_node) {
                 Ok(_) => {
                     let new_tree = Self::new(&new_node, xtree);
-                    parent.4.insert(child_idx, new_tree);
+                    parent.4.insert(child_idx + 1, new_tree);
                     return Ok(new_node);
                 },
                 Err(_) => {
@@ -749,7 +1125,7 @@ impl SaveNodeTree {
     }
 
     pub fn remove_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node) -> Result<(), String> {
-        if let Ok((parent, child_idx)) = Self::find_parent_childidx_from_node(root, node) {
+        if let Ok((parent, child_idx)) = Self::find_parent_childidx_from_node(root, xtree, node) {
             let node_deref = node.clone();
             match xtree.remove(node_deref) {
                 Ok(_) => {
@@ -765,4 +1141,375 @@ impl SaveNodeTree {
         }
     }
 
-}
\ No newline at end of file
+
+    pub fn find_node_mut<'a>(root: &'a mut Self, xtree: &xot::Xot, node: &xot::Node) -> Option<&'a mut Self> {
+        let position = Self::get_tree_position(root, xtree, node)?;
+        Self::get_at_position_mut(root, &position)
+    }
+
+    /// Rebuilds the children of the tree item for node after its xml children changed.
+    pub fn reload_children(root: &mut Self, xtree: &xot::Xot, node: &xot::Node) -> Result<(), String> {
+        match Self::find_node_mut(root, xtree, node) {
+            Some(tree_item) => {
+                tree_item.4 = Self::get_good_children_from_node(node, xtree);
+                tree_item.5 = true;
+                Ok(())
+            },
+            None => Err("Could not find node in tree.".to_string()),
+        }
+    }
+
+    pub fn is_valid_element_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(c) if c.is_alphabetic() || c == '_' => chars.all(|c| c.is_alphanumeric() || ['_', '-', '.'].contains(&c)),
+            _ => false,
+        }
+    }
+
+    fn get_parent_element(xtree: &xot::Xot, node: &xot::Node) -> Result<xot::Node, String> {
+        match xtree.parent(*node) {
+            Some(parent) if xtree.is_element(parent) => Ok(parent),
+            _ => Err("Node has no parent element.".to_string()),
+        }
+    }
+
+    pub fn rename_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, new_name: &str) -> Result<(), String> {
+        if !Self::is_valid_element_name(new_name) {return Err(format!("Invalid element name '{}'.", new_name))};
+        if !xtree.is_element(*node) {return Err("Only elements can be renamed.".to_string())};
+        let name_id = xtree.add_name(new_name);
+        xtree.set_element_name(*node, name_id);
+        match Self::find_node_mut(root, xtree, node) {
+            Some(tree_item) => {
+                tree_item.1 = new_name.to_string();
+                Ok(())
+            },
+            None => Err("Could not find node in tree.".to_string()),
+        }
+    }
+
+    pub fn insert_new_child(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, name: &str) -> Result<xot::Node, String> {
+        if !Self::is_valid_element_name(name) {return Err(format!("Invalid element name '{}'.", name))};
+        if !xtree.is_element(*node) {return Err("Can only add children to elements.".to_string())};
+        let name_id = xtree.add_name(name);
+        let new_node = xtree.new_element(name_id);
+        if xtree.append(*node, new_node).is_err() {
+            let _ = xtree.remove(new_node);
+            return Err("Could not add node to tree.".to_string());
+        };
+        Self::reload_children(root, xtree, node)?;
+        Ok(new_node)
+    }
+
+    pub fn insert_new_sibling(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, name: &str) -> Result<xot::Node, String> {
+        if !Self::is_valid_element_name(name) {return Err(format!("Invalid element name '{}'.", name))};
+        let parent_node = Self::get_parent_element(xtree, node)?;
+        let name_id = xtree.add_name(name);
+        let new_node = xtree.new_element(name_id);
+        if xtree.insert_after(*node, new_node).is_err() {
+            let _ = xtree.remove(new_node);
+            return Err("Could not add node to tree.".to_string());
+        };
+        Self::reload_children(root, xtree, &parent_node)?;
+        Ok(new_node)
+    }
+
+    /// Swaps node with the previous (or next) sibling shown in the tree, whitespace only text is skipped.
+    pub fn move_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, b_up: bool) -> Result<(), String> {
+        let (parent, child_idx) = Self::find_parent_childidx_from_node(root, xtree, node)?;
+        let sibling_idx = match b_up {
+            true => child_idx.checked_sub(1),
+            false => Some(child_idx + 1).filter(|x| *x < parent.4.len()),
+        };
+        let Some(sibling_idx) = sibling_idx else {return Err("Node can not be moved further.".to_string())};
+        let sibling_node = parent.4[sibling_idx].0;
+
+        if xtree.detach(*node).is_err() {return Err("Could not move node.".to_string())};
+        let insert_result = match b_up {
+            true => xtree.insert_before(sibling_node, *node),
+            false => xtree.insert_after(sibling_node, *node),
+        };
+        if insert_result.is_err() {
+            return Err("Could not move node, node is detached, please reload.".to_string());
+        };
+        parent.4.swap(child_idx, sibling_idx);
+        Ok(())
+    }
+
+    pub fn wrap_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, wrapper_name: &str) -> Result<xot::Node, String> {
+        if !Self::is_valid_element_name(wrapper_name) {return Err(format!("Invalid element name '{}'.", wrapper_name))};
+        let parent_node = Self::get_parent_element(xtree, node)?;
+        let name_id = xtree.add_name(wrapper_name);
+        let wrapper_node = xtree.element_wrap(*node, name_id).map_err(|e| format!("Could not wrap node. {}", e))?;
+        Self::reload_children(root, xtree, &parent_node)?;
+        Ok(wrapper_node)
+    }
+
+    /// Replaces node with its children, node is removed from the xml tree.
+    pub fn unwrap_node(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node) -> Result<(), String> {
+        let parent_node = Self::get_parent_element(xtree, node)?;
+        xtree.element_unwrap(*node).map_err(|e| format!("Could not unwrap node. {}", e))?;
+        Self::reload_children(root, xtree, &parent_node)
+    }
+
+    /// Attributes as (prefixed name, value), e.g. ("xsi:nil", "true").
+    pub fn get_attributes(xtree: &xot::Xot, node: &xot::Node) -> Vec<(String, String)> {
+        xtree.attributes(*node).iter()
+            .map(|(name_id, value)| {
+                let name = xtree.full_name(*node, name_id).unwrap_or_else(|_| xtree.local_name_str(name_id).to_string());
+                (name, value.clone())
+            })
+            .collect()
+    }
+
+    /// Resolves a possibly prefixed attribute name, the prefix must be declared on node or an ancestor.
+    fn get_attribute_name_id(xtree: &mut xot::Xot, node: &xot::Node, name: &str) -> Result<xot::NameId, String> {
+        match name.split_once(':') {
+            Some((prefix, local_name)) => {
+                if !Self::is_valid_element_name(local_name) {return Err(format!("Invalid attribute name '{}'.", name))};
+                let namespace_id = xtree.prefix(prefix)
+                    .and_then(|prefix_id| xtree.namespace_for_prefix(*node, prefix_id))
+                    .ok_or_else(|| format!("Namespace prefix '{}' is not declared.", prefix))?;
+                Ok(xtree.add_name_ns(local_name, namespace_id))
+            },
+            None => {
+                if !Self::is_valid_element_name(name) {return Err(format!("Invalid attribute name '{}'.", name))};
+                Ok(xtree.add_name(name))
+            },
+        }
+    }
+
+    pub fn set_attribute(xtree: &mut xot::Xot, node: &xot::Node, name: &str, value: &str) -> Result<(), String> {
+        if !xtree.is_element(*node) {return Err("Only elements have attributes.".to_string())};
+        let name_id = Self::get_attribute_name_id(xtree, node, name)?;
+        xtree.set_attribute(*node, name_id, value);
+        Ok(())
+    }
+
+    pub fn remove_attribute(xtree: &mut xot::Xot, node: &xot::Node, name: &str) -> Result<(), String> {
+        let name_id = Self::get_attribute_name_id(xtree, node, name)?;
+        xtree.remove_attribute(*node, name_id);
+        Ok(())
+    }
+
+    /// Serializes node and its descendants, namespaces used in the subtree are declared on the top element.
+    pub fn node_to_xml(xtree: &xot::Xot, node: &xot::Node) -> Result<String, String> {
+        xtree.to_string(*node).map_err(|e| format!("Could not write node as xml. {}", e))
+    }
+
+    /// Parses an xml fragment into unattached element nodes, the fragment may only contain elements and whitespace.
+    pub fn parse_xml_fragment(xtree: &mut xot::Xot, xml: &str) -> Result<Vec<xot::Node>, String> {
+        let fragment = xtree.parse_fragment(xml).map_err(|e| format!("Invalid xml. {}", e))?;
+        let children: Vec<xot::Node> = xtree.children(fragment).collect();
+        let b_only_elements = children.iter().all(|x| {
+            xtree.is_element(*x) || xtree.text_str(*x).is_some_and(|text| text.trim().is_empty())
+        });
+        let new_nodes: Vec<xot::Node> = children.into_iter().filter(|x| xtree.is_element(*x)).collect();
+
+        let error = match (b_only_elements, new_nodes.is_empty()) {
+            (false, _) => Some("Xml may only contain elements at the top level."),
+            (true, true) => Some("Xml contains no elements."),
+            (true, false) => None,
+        };
+        if let Some(error) = error {
+            let _ = xtree.remove(fragment);
+            return Err(error.to_string());
+        };
+        for new_node in new_nodes.iter() {
+            if xtree.detach(*new_node).is_err() {
+                let _ = xtree.remove(fragment);
+                return Err("Could not read xml elements.".to_string());
+            };
+        };
+        let _ = xtree.remove(fragment);
+        Ok(new_nodes)
+    }
+
+    /// Inserts the elements of an xml fragment as the last children of node, or as siblings after it.
+    pub fn paste_xml(root: &mut Self, xtree: &mut xot::Xot, node: &xot::Node, xml: &str, b_as_child: bool) -> Result<Vec<xot::Node>, String> {
+        let parent_node = match b_as_child {
+            true if xtree.is_element(*node) => *node,
+            true => return Err("Can only add children to elements.".to_string()),
+            false => Self::get_parent_element(xtree, node)?,
+        };
+        let new_nodes = Self::parse_xml_fragment(xtree, xml)?;
+
+        let mut insert_after_node = *node;
+        for (idx, new_node) in new_nodes.iter().enumerate() {
+            let insert_result = match b_as_child {
+                true => xtree.append(parent_node, *new_node),
+                false => xtree.insert_after(insert_after_node, *new_node),
+            };
+            if insert_result.is_err() {
+                new_nodes[idx..].iter().for_each(|x| {let _ = xtree.remove(*x);});
+                Self::reload_children(root, xtree, &parent_node)?;
+                return Err("Could not add xml to tree.".to_string());
+            };
+            insert_after_node = *new_node;
+        };
+
+        // pasted xml declares its own namespaces, drop the ones already declared above it
+        if let Some(doc_root) = xtree.ancestors(parent_node).last() {
+            xtree.deduplicate_namespaces(doc_root);
+        };
+        Self::reload_children(root, xtree, &parent_node)?;
+        Ok(new_nodes)
+    }
+
+}
+
+impl SaveChangeListener for SaveNodeTree {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, _lm: &LootManager, events: &[SaveChangeEvent]) {
+        let mut text_nodes: Vec<xot::Node> = Vec::new();
+        let mut reload_nodes: Vec<xot::Node> = Vec::new();
+        for event in events.iter() {
+            match event {
+                SaveChangeEvent::TextChanged(node) | SaveChangeEvent::NodeRenamed(node) => text_nodes.push(*node),
+                // attributes are read from the xml tree when shown
+                SaveChangeEvent::AttributeChanged(_) => {},
+                SaveChangeEvent::NodeInserted { parent, .. } | SaveChangeEvent::NodeRemoved { parent, .. } => reload_nodes.push(*parent),
+                SaveChangeEvent::NodeMoved(node) => {
+                    if let Some(parent) = sm.xtree.parent(*node) {reload_nodes.push(parent)};
+                },
+            };
+        };
+        let mut seen_nodes: HashSet<xot::Node> = HashSet::new();
+        reload_nodes.retain(|x| seen_nodes.insert(*x));
+        for node in reload_nodes.iter() {
+            if sm.xtree.is_removed(*node) {continue};
+            // branches that haven't been loaded are read from the xml tree when expanded
+            if let Some(tree_item) = Self::find_node_mut(self, &sm.xtree, node) && tree_item.5 {
+                tree_item.4 = Self::get_good_children_from_node(node, &sm.xtree);
+            };
+        };
+        text_nodes.retain(|x| !sm.xtree.is_removed(*x));
+        Self::update_strings_for_nodes(self, &sm.xtree, &text_nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES_XML: &str = "<SaveGame>\
+        <ToolLevelling><ToolLevel><type>Axe</type><level><int>2</int></level><flag>True</flag><count>3</count></ToolLevel>\
+        <ToolLevel><type>Hoe</type><level><int>4</int></level><flag>false</flag><count>2.5</count></ToolLevel></ToolLevelling>\
+        <Stats><xp>10</xp><xp>12</xp><speed><float>1</float></speed><ok><boolean>true</boolean></ok></Stats>\
+        <Ids><id>3f2504e0-4f89-11d3-9a0c-0305e82c3301</id></Ids>\
+        <Names><name>Old Mill</name><only>Axe</only><empty></empty></Names>\
+        </SaveGame>";
+
+    /// Inferred type of the first node matching the query in VALUES_XML.
+    fn infer_at(query: &str) -> SaveValueType {
+        let mut xtree = xot::Xot::new();
+        let root = xtree.parse(VALUES_XML).unwrap();
+        let doc_el = xtree.document_element(root).unwrap();
+        let node = SaveQuery::parse(query).unwrap().select(&xtree, doc_el)[0];
+        SaveValueType::infer(&xtree, node)
+    }
+
+    #[test]
+    fn infer_from_type_names() {
+        assert_eq!(infer_at("ToolLevelling/ToolLevel/level/int"), SaveValueType::Int);
+        assert_eq!(infer_at("Stats/speed/float"), SaveValueType::Float);
+        assert_eq!(infer_at("Stats/ok/boolean"), SaveValueType::Bool);
+    }
+
+    #[test]
+    fn infer_from_cousin_values() {
+        assert_eq!(infer_at("ToolLevelling/ToolLevel/type"), SaveValueType::Enum(vec!["Axe".to_string(), "Hoe".to_string()]));
+        assert_eq!(infer_at("ToolLevelling/ToolLevel/flag"), SaveValueType::Bool);
+        // a cousin holding 2.5 makes the field a float
+        assert_eq!(infer_at("ToolLevelling/ToolLevel/count"), SaveValueType::Float);
+        assert_eq!(infer_at("Stats/xp"), SaveValueType::Number);
+        assert_eq!(infer_at("Ids/id"), SaveValueType::Guid);
+    }
+
+    #[test]
+    fn infer_text_for_free_text_single_values_and_empty_nodes() {
+        assert_eq!(infer_at("Names/name"), SaveValueType::Text);
+        // one identifier isn't enough to call it an enum
+        assert_eq!(infer_at("Names/only"), SaveValueType::Text);
+        assert_eq!(infer_at("Names/empty"), SaveValueType::Text);
+    }
+
+    #[test]
+    fn validate_bool_normalises_casing() {
+        assert_eq!(SaveValueType::Bool.validate("true".to_string()), Some("true".to_string()));
+        assert_eq!(SaveValueType::Bool.validate(" False ".to_string()), Some("False".to_string()));
+        assert_eq!(SaveValueType::Bool.validate("TRUE".to_string()), Some("True".to_string()));
+        assert_eq!(SaveValueType::Bool.validate("fALSE".to_string()), Some("false".to_string()));
+        for value in ["yes", "1", "", "truth"] {
+            assert_eq!(SaveValueType::Bool.validate(value.to_string()), None, "{:?}", value);
+        };
+    }
+
+    #[test]
+    fn validate_numbers() {
+        assert_eq!(SaveValueType::Int.validate(" 42 ".to_string()), Some("42".to_string()));
+        assert_eq!(SaveValueType::Int.validate("-7".to_string()), Some("-7".to_string()));
+        assert_eq!(SaveValueType::Int.validate("4.2".to_string()), None);
+        assert_eq!(SaveValueType::Number.validate("2.5".to_string()), Some("2.5".to_string()));
+        assert_eq!(SaveValueType::Float.validate(" 1e3 ".to_string()), Some("1e3".to_string()));
+        for value in ["inf", "NaN", "abc", ""] {
+            assert_eq!(SaveValueType::Float.validate(value.to_string()), None, "{:?}", value);
+            assert_eq!(SaveValueType::Number.validate(value.to_string()), None, "{:?}", value);
+        };
+    }
+
+    #[test]
+    fn validate_guid_enum_and_text() {
+        let guid = "3f2504e0-4f89-11d3-9a0c-0305e82c3301";
+        assert_eq!(SaveValueType::Guid.validate(format!(" {} ", guid)), Some(guid.to_string()));
+        assert_eq!(SaveValueType::Guid.validate("3f2504e0-4f89-11d3-9a0c".to_string()), None);
+        let value_type = SaveValueType::Enum(vec!["Axe".to_string(), "Hoe".to_string()]);
+        // values not seen yet are allowed, as long as they're one word
+        assert_eq!(value_type.validate("Sickle".to_string()), Some("Sickle".to_string()));
+        assert_eq!(value_type.validate("two words".to_string()), None);
+        assert_eq!(value_type.validate(" ".to_string()), None);
+        assert_eq!(SaveValueType::Text.validate(" keeps spaces ".to_string()), Some(" keeps spaces ".to_string()));
+    }
+
+    /// SaveDataManager holding VALUES_XML, without the inventory refs load_data finds.
+    fn values_save() -> SaveDataManager {
+        let mut sm = SaveDataManager::default();
+        let root = sm.xtree.parse(VALUES_XML).unwrap();
+        sm.root = Some(root);
+        sm.doc_el = Some(sm.xtree.document_element(root).unwrap());
+        sm
+    }
+
+    #[test]
+    fn query_set_str_sets_leaves_that_suit_the_value() {
+        let mut sm = values_save();
+        assert_eq!(sm.query_set_str("ToolLevelling/ToolLevel/level/int", " 9 ").unwrap(), 2);
+        assert_eq!(sm.changes.take().len(), 2);
+        let texts: Vec<String> = sm.query_get_strs("ToolLevelling/ToolLevel/level/int").unwrap().into_iter().map(|x| x.1).collect();
+        assert_eq!(texts, vec!["9", "9"]);
+        // nodes with child elements are skipped
+        assert_eq!(sm.query_set_str("ToolLevelling/ToolLevel/level", "1").unwrap(), 0);
+        assert_eq!(sm.query_set_str("ToolLevelling/ToolLevel/flag", "TRUE").unwrap(), 2);
+        assert_eq!(sm.query_get_strs("ToolLevelling/ToolLevel[1]/flag").unwrap()[0].1, "True");
+    }
+
+    #[test]
+    fn query_set_str_sets_nothing_if_any_leaf_is_unsuited() {
+        let mut sm = values_save();
+        let error = sm.query_set_str("ToolLevelling/ToolLevel/*", "5").unwrap_err().to_string();
+        assert!(error.contains("ToolLevelling/ToolLevel[1]/flag (bool)"), "{}", error);
+        assert!(sm.changes.take().is_empty());
+        assert_eq!(sm.query_get_strs("ToolLevelling/ToolLevel[2]/count").unwrap()[0].1, "2.5");
+        assert!(sm.query_set_str("Stats/xp", "lots").is_err());
+    }
+
+    #[test]
+    fn text_like_keeps_the_save_style() {
+        assert_eq!(bool_text_like(true, "False"), "True");
+        assert_eq!(bool_text_like(false, "true"), "false");
+        assert_eq!(float_text_like(2.0, "1.50"), "2.00");
+        assert_eq!(float_text_like(2.25, "1.5"), "2.25");
+        assert_eq!(float_text_like(3.0, "1"), "3");
+        assert_eq!(float_text_like(0.1 + 0.2, "0"), "0.3");
+    }
+}
@@ -0,0 +1,298 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::savedata::{SaveDataManager, SaveInventoryItemRef};
+
+/// Every value that couldn't be read or written, one message each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveModelError {
+    pub problems: Vec<String>,
+}
+
+impl std::fmt::Display for SaveModelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.problems.join("; "))
+    }
+}
+
+impl Error for SaveModelError {}
+
+impl SaveModelError {
+
+    fn new(problem: String) -> Self {
+        Self { problems: vec![problem] }
+    }
+}
+
+/// Parses the text of node as T, the error names the node's path and text.
+pub fn parse_node<T: FromStr>(xtree: &xot::Xot, node: xot::Node) -> Result<T, String> {
+    let text = xtree.text_content_str(node).unwrap_or_default();
+    text.trim().parse::<T>().map_err(|_| {
+        let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+        format!("{} {:?} is not a {}", SaveDataManager::get_node_path_xt(xtree, node), text, type_name)
+    })
+}
+
+/// Writes value to node as text only if the node holds a different value,
+/// so untouched values keep their formatting and don't raise change events. Returns true if written.
+pub fn write_node_if_changed<T: FromStr + PartialEq + ToString>(sm: &mut SaveDataManager, node: xot::Node, value: &T) -> bool {
+    let save_value = sm.xtree.text_content_str(node).and_then(|x| x.trim().parse::<T>().ok());
+    if save_value.as_ref() == Some(value) {return false};
+    sm.set_node_text(node, value.to_string()).is_some()
+}
+
+
+/// Value of a characterStats entry, integers stay integers when written back.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum StatValue {
+    Integer(i64),
+    Decimal(f64),
+}
+
+impl Default for StatValue {
+    fn default() -> Self {
+        StatValue::Integer(0)
+    }
+}
+
+impl FromStr for StatValue {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(x) = s.parse::<i64>() {return Ok(StatValue::Integer(x))};
+        match s.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(StatValue::Decimal(x)),
+            _ => Err(format!("{:?} is not a number", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for StatValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StatValue::Integer(x) => write!(f, "{}", x),
+            StatValue::Decimal(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+impl StatValue {
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            StatValue::Integer(x) => *x as f64,
+            StatValue::Decimal(x) => *x,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Stat {
+    /// element name in characterStats, e.g. BASE_STRENGTH
+    pub name: String,
+    pub value: StatValue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ToolLevel {
+    pub tool_type: String,
+    pub level: u8,
+    pub current_xp: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ItemStack {
+    pub uid: i32,
+    /// quantity per star rating, 1 star first
+    pub counts: Vec<i32>,
+}
+
+impl ItemStack {
+
+    pub fn read(xtree: &xot::Xot, item_ref: &SaveInventoryItemRef) -> Result<Self, Vec<String>> {
+        let uid = parse_node::<i32>(xtree, item_ref.key_int_node);
+        let counts: Vec<Result<i32, String>> = item_ref.count_int_nodes.iter().map(|x| parse_node::<i32>(xtree, *x)).collect();
+        let problems: Vec<String> = std::iter::once(uid.clone().err())
+            .chain(counts.iter().map(|x| x.clone().err()))
+            .flatten()
+            .collect();
+        match problems.is_empty() {
+            true => Ok(Self { uid: uid.unwrap_or_default(), counts: counts.into_iter().flatten().collect() }),
+            false => Err(problems),
+        }
+    }
+}
+
+
+pub struct ToolLevelRef {
+    pub tool_node: xot::Node,
+    pub tool_type_node: xot::Node,
+    pub tool_level_node: xot::Node,
+    pub tool_current_xp_node: xot::Node,
+}
+
+/// # PlayerDataNodes
+/// Where the values of a PlayerData are in the save.
+#[derive(Default)]
+pub struct PlayerDataNodes {
+    pub brass_count_node: Option<xot::Node>,
+    pub character_stats_node: Option<xot::Node>,
+    /// BASE_ stats and any other characterStats value that is a number
+    pub stats_nodes: Vec<xot::Node>,
+    pub tool_levelling_node: Option<xot::Node>,
+    pub tool_level_ref: Vec<ToolLevelRef>,
+}
+
+impl PlayerDataNodes {
+
+    pub fn locate(sm: &SaveDataManager) -> Result<Self, SaveModelError> {
+        let mut nodes = Self::default();
+        let mut problems: Vec<String> = Vec::new();
+        if let Err(e) = nodes.locate_brass_stats(sm) {problems.extend(e.problems)};
+        if let Err(e) = nodes.locate_tool_levels(sm) {problems.extend(e.problems)};
+        match problems.is_empty() {
+            true => Ok(nodes),
+            false => Err(SaveModelError { problems }),
+        }
+    }
+
+    pub fn locate_brass_stats(&mut self, sm: &SaveDataManager) -> Result<(), SaveModelError> {
+        let playerdata_node = sm.playerdata_node.ok_or_else(|| SaveModelError::new("no PlayerData".to_string()))?;
+        self.brass_count_node = Some(sm.get_child_node_from_name(playerdata_node, "BrassCount")
+            .ok_or_else(|| SaveModelError::new("no PlayerData/BrassCount".to_string()))?);
+        let character_stats_node = sm.get_child_node_from_name(playerdata_node, "characterStats")
+            .ok_or_else(|| SaveModelError::new("no PlayerData/characterStats".to_string()))?;
+        self.character_stats_node = Some(character_stats_node);
+        // BASE_ stats are always included so a bad value is reported rather than hidden
+        self.stats_nodes = sm.xtree.children(character_stats_node)
+            .filter(|x| sm.xtree.is_element(*x) && !sm.xtree.children(*x).any(|child| sm.xtree.is_element(child)))
+            .filter(|x| sm.get_name_from_node(*x).is_some_and(|name| name.contains("BASE_"))
+                || sm.xtree.text_content_str(*x).is_some_and(|text| text.parse::<StatValue>().is_ok()))
+            .collect();
+        Ok(())
+    }
+
+    pub fn locate_tool_levels(&mut self, sm: &SaveDataManager) -> Result<(), SaveModelError> {
+        let playerdata_node = sm.playerdata_node.ok_or_else(|| SaveModelError::new("no PlayerData".to_string()))?;
+        let tool_levelling_node = sm.get_child_node_from_name(playerdata_node, "ToolLevelling")
+            .ok_or_else(|| SaveModelError::new("no PlayerData/ToolLevelling".to_string()))?;
+        self.tool_levelling_node = Some(tool_levelling_node);
+        self.tool_level_ref.clear();
+        for child in sm.xtree.children(tool_levelling_node) {
+            if sm.get_name_from_node(child) != Some("ToolLevel") {continue};
+            let get_child = |name: &str| sm.get_child_node_from_name(child, name)
+                .ok_or_else(|| SaveModelError::new(format!("no {} in {}", name, SaveDataManager::get_node_path_xt(&sm.xtree, child))));
+            self.tool_level_ref.push(ToolLevelRef {
+                tool_node: child,
+                tool_type_node: get_child("type")?,
+                tool_level_node: get_child("Level")?,
+                tool_current_xp_node: get_child("ExactCurrentXP")?,
+            });
+        };
+        Ok(())
+    }
+
+    pub fn read_brass(&self, xtree: &xot::Xot) -> Result<u32, String> {
+        parse_node(xtree, self.brass_count_node.ok_or("no PlayerData/BrassCount")?)
+    }
+
+    pub fn read_stats(&self, xtree: &xot::Xot) -> Result<Vec<Stat>, Vec<String>> {
+        collect_problems(self.stats_nodes.iter().map(|x| {
+            let name = SaveDataManager::get_name_from_node_xt(xtree, *x).unwrap_or_default().to_string();
+            parse_node(xtree, *x).map(|value| Stat { name, value })
+        }))
+    }
+
+    pub fn read_tools(&self, xtree: &xot::Xot) -> Result<Vec<ToolLevel>, Vec<String>> {
+        collect_problems(self.tool_level_ref.iter().map(|x| {
+            let tool_type = xtree.text_content_str(x.tool_type_node).unwrap_or_default().to_string();
+            let level = parse_node(xtree, x.tool_level_node)?;
+            let current_xp = parse_node(xtree, x.tool_current_xp_node)?;
+            Ok(ToolLevel { tool_type, level, current_xp })
+        }))
+    }
+}
+
+/// Ok with every value, or Err with every problem.
+fn collect_problems<T>(results: impl Iterator<Item = Result<T, String>>) -> Result<Vec<T>, Vec<String>> {
+    let mut values: Vec<T> = Vec::new();
+    let mut problems: Vec<String> = Vec::new();
+    for result in results {
+        match result {
+            Ok(x) => values.push(x),
+            Err(s) => problems.push(s),
+        };
+    };
+    match problems.is_empty() {
+        true => Ok(values),
+        false => Err(problems),
+    }
+}
+
+fn read_item_stacks(xtree: &xot::Xot, item_refs: &[SaveInventoryItemRef]) -> Result<Vec<ItemStack>, Vec<String>> {
+    let mut problems: Vec<String> = Vec::new();
+    let stacks: Vec<ItemStack> = item_refs.iter()
+        .filter_map(|x| ItemStack::read(xtree, x).map_err(|e| problems.extend(e)).ok())
+        .collect();
+    match problems.is_empty() {
+        true => Ok(stacks),
+        false => Err(problems),
+    }
+}
+
+/// # PlayerData
+/// Plain copy of the save values the editor knows about, read with PlayerData::extract.
+/// The editor's sections write their own values back, see savesections.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PlayerData {
+    pub brass: u32,
+    pub stats: Vec<Stat>,
+    pub tools: Vec<ToolLevel>,
+    /// PlayerData/Inventory/AllItems
+    pub inventory: Vec<ItemStack>,
+    /// ItemStacks in PlayerData/newLarder
+    pub larder: Vec<ItemStack>,
+    /// ItemStacks in SavedShops
+    pub shops: Vec<ItemStack>,
+}
+
+impl PlayerData {
+
+    /// Reads a loaded save, the error lists every value that is missing or doesn't parse.
+    pub fn extract(sm: &SaveDataManager) -> Result<Self, SaveModelError> {
+        let nodes = PlayerDataNodes::locate(sm)?;
+        let mut problems: Vec<String> = Vec::new();
+        let mut player_data = Self::default();
+
+        match nodes.read_brass(&sm.xtree) {
+            Ok(x) => player_data.brass = x,
+            Err(s) => problems.push(s),
+        };
+        match nodes.read_stats(&sm.xtree) {
+            Ok(x) => player_data.stats = x,
+            Err(e) => problems.extend(e),
+        };
+        match nodes.read_tools(&sm.xtree) {
+            Ok(x) => player_data.tools = x,
+            Err(e) => problems.extend(e),
+        };
+        for (item_refs, stacks) in [
+            (&sm.save_inventory_ref, &mut player_data.inventory),
+            (&sm.newlarder_item_ref, &mut player_data.larder),
+            (&sm.savedshops_item_ref, &mut player_data.shops),
+        ] {
+            match read_item_stacks(&sm.xtree, item_refs) {
+                Ok(x) => *stacks = x,
+                Err(e) => problems.extend(e),
+            };
+        };
+
+        match problems.is_empty() {
+            true => Ok(player_data),
+            false => Err(SaveModelError { problems }),
+        }
+    }
+}
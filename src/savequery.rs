@@ -0,0 +1,373 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::savedata::SaveDataManager;
+
+#[derive(Debug, Clone)]
+pub struct SaveQueryError(pub String);
+
+impl std::fmt::Display for SaveQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Invalid query: {}", self.0)
+    }
+}
+
+impl Error for SaveQueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryPredicate {
+    /// [3], 1-based position among the nodes matched by the step under the same parent
+    Position(usize),
+    /// [key/int], has at least one node at the relative path
+    Exists(Vec<String>),
+    /// [key/int=512], [.!=0], compares the text of nodes at the relative path, true if any match
+    Compare(Vec<String>, CompareOp, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryStep {
+    /// element name, "*" for any element, "." for self, ".." for parent
+    pub name: String,
+    /// step was preceded by "//", matches descendants instead of children
+    pub b_descendant: bool,
+    pub predicates: Vec<QueryPredicate>,
+}
+
+/// # SaveQuery
+/// A small XPath-like path over the save tree, e.g.
+/// `PlayerData/Inventory/AllItems/item[key/int=512]/value/InventoryItem/Count/int[3]`
+/// - relative paths start below the document element, a leading "/" starts at the document element itself
+/// - `//name` matches descendants at any depth, `*` any element, `.` self, `..` parent
+/// - predicates `[n]`, `[relpath]`, `[relpath=value]` with `= != > >= < <=`, values can be quoted
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveQuery {
+    pub b_absolute: bool,
+    pub steps: Vec<QueryStep>,
+}
+
+impl SaveQuery {
+
+    pub fn parse(query: &str) -> Result<Self, SaveQueryError> {
+        let chars: Vec<char> = query.trim().chars().collect();
+        if chars.is_empty() {return Err(SaveQueryError("empty query".to_string()))};
+
+        let mut idx = 0;
+        let mut b_absolute = false;
+        let mut steps: Vec<QueryStep> = Vec::new();
+        let mut b_descendant = false;
+
+        if chars.starts_with(&['/', '/']) {
+            b_descendant = true;
+            idx = 2;
+        } else if chars[0] == '/' {
+            b_absolute = true;
+            idx = 1;
+        };
+
+        loop {
+            let name = parse_name(&chars, &mut idx);
+            if name.is_empty() {
+                return Err(SaveQueryError(format!("expected element name at position {}", idx)));
+            };
+            let mut predicates: Vec<QueryPredicate> = Vec::new();
+            while chars.get(idx) == Some(&'[') {
+                predicates.push(parse_predicate(&chars, &mut idx)?);
+            };
+            steps.push(QueryStep { name, b_descendant, predicates });
+
+            match (chars.get(idx), chars.get(idx + 1)) {
+                (None, _) => break,
+                (Some('/'), Some('/')) => {b_descendant = true; idx += 2;},
+                (Some('/'), _) => {b_descendant = false; idx += 1;},
+                (Some(c), _) => return Err(SaveQueryError(format!("unexpected '{}' at position {}", c, idx))),
+            };
+        };
+
+        Ok(Self { b_absolute, steps })
+    }
+
+    /// Evaluates the query against the document element, returns matching nodes in document order without duplicates.
+    pub fn select(&self, xtree: &xot::Xot, doc_el: xot::Node) -> Vec<xot::Node> {
+        let mut context: Vec<xot::Node> = match self.b_absolute {
+            // the document element is the only child of the document root
+            true => xtree.parent(doc_el).into_iter().collect(),
+            false => vec![doc_el],
+        };
+        for step in self.steps.iter() {
+            let mut next_context: Vec<xot::Node> = Vec::new();
+            let mut seen: HashSet<xot::Node> = HashSet::new();
+            for node in context.iter() {
+                for matched in select_step(xtree, *node, step) {
+                    if seen.insert(matched) {next_context.push(matched)};
+                };
+            };
+            context = next_context;
+        };
+        context
+    }
+
+    /// True if every step names the element to go to, i.e. no "//" or "*" step,
+    /// so the path points at one place in the save rather than searching for it.
+    pub fn is_exact(&self) -> bool {
+        self.steps.iter().all(|x| !x.b_descendant && x.name != "*")
+    }
+}
+
+fn parse_name(chars: &[char], idx: &mut usize) -> String {
+    let mut name = String::new();
+    if chars.get(*idx) == Some(&'*') {
+        *idx += 1;
+        return "*".to_string();
+    };
+    while let Some(c) = chars.get(*idx) {
+        if c.is_alphanumeric() || ['_', '-', '.', ':'].contains(c) {
+            name.push(*c);
+            *idx += 1;
+        } else {
+            break;
+        };
+    };
+    name
+}
+
+fn parse_predicate(chars: &[char], idx: &mut usize) -> Result<QueryPredicate, SaveQueryError> {
+    // skip '['
+    *idx += 1;
+    let start = *idx;
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.get(*idx) {
+        match quote {
+            Some(q) if *c == q => quote = None,
+            Some(_) => {},
+            None if *c == '"' || *c == '\'' => quote = Some(*c),
+            None if *c == ']' => break,
+            None => {},
+        };
+        *idx += 1;
+    };
+    if chars.get(*idx) != Some(&']') {
+        return Err(SaveQueryError(format!("unclosed '[' at position {}", start - 1)));
+    };
+    let inner: String = chars[start..*idx].iter().collect();
+    *idx += 1;
+    let inner = inner.trim();
+
+    if let Ok(position) = inner.parse::<usize>() {
+        if position == 0 {return Err(SaveQueryError("positions start at 1".to_string()))};
+        return Ok(QueryPredicate::Position(position));
+    };
+
+    if let Some((path_str, op, value_str)) = split_compare(inner) {
+        let rel_path = parse_rel_path(path_str)?;
+        let value = value_str.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+        return Ok(QueryPredicate::Compare(rel_path, op, value));
+    };
+
+    Ok(QueryPredicate::Exists(parse_rel_path(inner)?))
+}
+
+/// Splits a predicate at its first comparison operator outside quotes, e.g. `name="a!=b"` at the `=`.
+fn split_compare(inner: &str) -> Option<(&str, CompareOp, &str)> {
+    let mut quote: Option<char> = None;
+    for (idx, c) in inner.char_indices() {
+        match quote {
+            Some(q) if c == q => {quote = None; continue},
+            Some(_) => continue,
+            None if c == '"' || c == '\'' => {quote = Some(c); continue},
+            None => {},
+        };
+        let rest = &inner[idx..];
+        for (op_str, op) in [("!=", CompareOp::Ne), (">=", CompareOp::Ge), ("<=", CompareOp::Le),
+            ("=", CompareOp::Eq), (">", CompareOp::Gt), ("<", CompareOp::Lt)]
+        {
+            if let Some(value_str) = rest.strip_prefix(op_str) {
+                return Some((&inner[..idx], op, value_str));
+            };
+        };
+    };
+    None
+}
+
+fn parse_rel_path(path_str: &str) -> Result<Vec<String>, SaveQueryError> {
+    let rel_path: Vec<String> = path_str.trim().split('/').map(|x| x.trim().to_string()).collect();
+    if rel_path.iter().any(|x| x.is_empty()) {
+        return Err(SaveQueryError(format!("invalid path in predicate '{}'", path_str)));
+    };
+    Ok(rel_path)
+}
+
+fn is_name_match(xtree: &xot::Xot, node: xot::Node, name: &str) -> bool {
+    match SaveDataManager::get_name_from_node_xt(xtree, node) {
+        None => false,
+        Some(node_name) => name == "*" || node_name == name,
+    }
+}
+
+fn select_step(xtree: &xot::Xot, node: xot::Node, step: &QueryStep) -> Vec<xot::Node> {
+    let mut candidates: Vec<xot::Node> = match step.name.as_str() {
+        "." => vec![node],
+        ".." => xtree.parent(node).into_iter().filter(|x| xtree.is_element(*x)).collect(),
+        name => match step.b_descendant {
+            true => xtree.descendants(node).skip(1).filter(|x| is_name_match(xtree, *x, name)).collect(),
+            false => xtree.children(node).filter(|x| is_name_match(xtree, *x, name)).collect(),
+        },
+    };
+
+    for predicate in step.predicates.iter() {
+        candidates = match predicate {
+            QueryPredicate::Position(position) => {
+                // positions count per parent, so "//item[1]" gives the first item under each parent
+                let mut parent_counts: HashMap<Option<xot::Node>, usize> = HashMap::new();
+                candidates.into_iter()
+                    .filter(|x| {
+                        let count = parent_counts.entry(xtree.parent(*x)).or_default();
+                        *count += 1;
+                        *count == *position
+                    })
+                    .collect()
+            },
+            QueryPredicate::Exists(rel_path) => {
+                candidates.into_iter().filter(|x| !select_rel_path(xtree, *x, rel_path).is_empty()).collect()
+            },
+            QueryPredicate::Compare(rel_path, op, value) => {
+                candidates.into_iter()
+                    .filter(|x| {
+                        select_rel_path(xtree, *x, rel_path).iter()
+                            .any(|y| compare_text(xtree.text_content_str(*y).unwrap_or(""), *op, value))
+                    })
+                    .collect()
+            },
+        };
+    };
+    candidates
+}
+
+fn select_rel_path(xtree: &xot::Xot, node: xot::Node, rel_path: &[String]) -> Vec<xot::Node> {
+    let mut context: Vec<xot::Node> = vec![node];
+    for name in rel_path.iter() {
+        let step = QueryStep { name: name.clone(), b_descendant: false, predicates: Vec::new() };
+        context = context.iter().flat_map(|x| select_step(xtree, *x, &step)).collect();
+    };
+    context
+}
+
+/// Compares as numbers if both sides parse, otherwise as strings.
+fn compare_text(text: &str, op: CompareOp, value: &str) -> bool {
+    let ordering = match (text.trim().parse::<f64>(), value.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => Some(text.trim().cmp(value)),
+    };
+    let Some(ordering) = ordering else {return false};
+    match op {
+        CompareOp::Eq => ordering.is_eq(),
+        CompareOp::Ne => ordering.is_ne(),
+        CompareOp::Gt => ordering.is_gt(),
+        CompareOp::Ge => ordering.is_ge(),
+        CompareOp::Lt => ordering.is_lt(),
+        CompareOp::Le => ordering.is_le(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAVE_XML: &str = "<SaveGame><PlayerData><Inventory>\
+        <item><key><int>512</int></key><value>3</value></item>\
+        <item><key><int>7</int></key><value>0</value></item>\
+        <item><key><int>64</int></key><value>12</value></item>\
+        </Inventory><Larder><item><key><int>512</int></key><value>1</value></item></Larder>\
+        <name>a!=b</name></PlayerData></SaveGame>";
+
+    /// Text of each node the query selects in SAVE_XML.
+    fn select_texts(query: &str) -> Vec<String> {
+        let mut xtree = xot::Xot::new();
+        let root = xtree.parse(SAVE_XML).unwrap();
+        let doc_el = xtree.document_element(root).unwrap();
+        SaveQuery::parse(query).unwrap().select(&xtree, doc_el).into_iter()
+            .map(|x| xtree.text_content_str(x).unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parse_steps_and_predicates() {
+        let query = SaveQuery::parse("PlayerData/Inventory/item[key/int=512][2]//value").unwrap();
+        assert!(!query.b_absolute);
+        assert_eq!(query.steps.len(), 4);
+        assert_eq!(query.steps[2].predicates, vec![
+            QueryPredicate::Compare(vec!["key".to_string(), "int".to_string()], CompareOp::Eq, "512".to_string()),
+            QueryPredicate::Position(2),
+        ]);
+        assert!(query.steps[3].b_descendant);
+        assert!(SaveQuery::parse("/SaveGame/PlayerData").unwrap().b_absolute);
+        assert_eq!(SaveQuery::parse("item[key]").unwrap().steps[0].predicates, vec![QueryPredicate::Exists(vec!["key".to_string()])]);
+    }
+
+    #[test]
+    fn parse_quoted_value_keeps_operators() {
+        let query = SaveQuery::parse("PlayerData[name='a!=b']").unwrap();
+        assert_eq!(query.steps[0].predicates, vec![QueryPredicate::Compare(vec!["name".to_string()], CompareOp::Eq, "a!=b".to_string())]);
+        let query = SaveQuery::parse("item[value=\"]\"]").unwrap();
+        assert_eq!(query.steps[0].predicates, vec![QueryPredicate::Compare(vec!["value".to_string()], CompareOp::Eq, "]".to_string())]);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_queries() {
+        for query in ["", "  ", "item[1", "item[0]", "item[]", "item[a//b]", "item/", "item]", "a b"] {
+            assert!(SaveQuery::parse(query).is_err(), "{:?} should not parse", query);
+        };
+    }
+
+    #[test]
+    fn split_compare_finds_first_operator_outside_quotes() {
+        assert_eq!(split_compare("key/int=512"), Some(("key/int", CompareOp::Eq, "512")));
+        assert_eq!(split_compare(".!=0"), Some((".", CompareOp::Ne, "0")));
+        assert_eq!(split_compare("value>=10"), Some(("value", CompareOp::Ge, "10")));
+        assert_eq!(split_compare("value<3"), Some(("value", CompareOp::Lt, "3")));
+        assert_eq!(split_compare("name='a!=b'"), Some(("name", CompareOp::Eq, "'a!=b'")));
+        assert_eq!(split_compare("'a=b'"), None);
+        assert_eq!(split_compare("key/int"), None);
+    }
+
+    #[test]
+    fn is_exact_only_without_descendant_or_wildcard_steps() {
+        assert!(SaveQuery::parse("PlayerData/Inventory/item[2]/value").unwrap().is_exact());
+        assert!(!SaveQuery::parse("PlayerData//value").unwrap().is_exact());
+        assert!(!SaveQuery::parse("//value").unwrap().is_exact());
+        assert!(!SaveQuery::parse("PlayerData/*/item").unwrap().is_exact());
+    }
+
+    #[test]
+    fn select_children_positions_and_compares() {
+        assert_eq!(select_texts("PlayerData/Inventory/item/value"), vec!["3", "0", "12"]);
+        assert_eq!(select_texts("PlayerData/Inventory/item[2]/value"), vec!["0"]);
+        assert_eq!(select_texts("PlayerData/Inventory/item[key/int=512]/value"), vec!["3"]);
+        // compared as numbers when both sides parse, so 12 > 4
+        assert_eq!(select_texts("PlayerData/Inventory/item[value>4]/key/int"), vec!["64"]);
+        assert_eq!(select_texts("PlayerData/Inventory/item/value[.!=0]"), vec!["3", "12"]);
+        assert_eq!(select_texts("PlayerData[name='a!=b']/Larder/item/value"), vec!["1"]);
+        assert!(select_texts("PlayerData/Inventory/item[4]").is_empty());
+        assert!(select_texts("Inventory").is_empty());
+    }
+
+    #[test]
+    fn select_absolute_descendants_and_parents() {
+        assert_eq!(select_texts("/SaveGame/PlayerData/Larder/item/value"), vec!["1"]);
+        assert!(select_texts("/PlayerData").is_empty());
+        // positions count per parent, results are in document order without duplicates
+        assert_eq!(select_texts("//item[1]/value"), vec!["3", "1"]);
+        assert_eq!(select_texts("//item[key/int=512]/value"), vec!["3", "1"]);
+        assert_eq!(select_texts("PlayerData//int/../../value"), vec!["3", "0", "12", "1"]);
+        assert_eq!(select_texts("PlayerData/*/item[3]/value"), vec!["12"]);
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::savedata::{SaveDataManager, SaveNodeTree};
+
+/// # SaveTreeSearch
+/// A parsed search query for the save tree.
+/// - `>500`, `<=12`, `=3`, `!=0` compare numeric values
+/// - `/pattern/` is a regex, in the syntax of the regex crate (case-sensitive unless it starts with `(?i)`)
+/// - anything else is a case-insensitive substring
+#[derive(Debug, Clone)]
+pub enum SaveTreeSearch {
+    Substring(String),
+    Regex(regex::Regex),
+    Numeric(NumericOp, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+/// Which parts of a node a search is matched against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SaveTreeSearchScope {
+    pub names: bool,
+    pub values: bool,
+    pub paths: bool,
+}
+
+impl Default for SaveTreeSearchScope {
+    fn default() -> Self {
+        Self {
+            names: true,
+            values: true,
+            paths: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SaveTreeSearchResult {
+    pub node: xot::Node,
+    pub path: String,
+    pub text: String,
+}
+
+impl SaveTreeSearch {
+
+    /// Fails only for a regex that doesn't compile.
+    pub fn parse(query: &str) -> Result<Self, regex::Error> {
+        let query = query.trim();
+        for (op_str, op) in [(">=", NumericOp::Ge), ("<=", NumericOp::Le), ("!=", NumericOp::Ne),
+            (">", NumericOp::Gt), ("<", NumericOp::Lt), ("=", NumericOp::Eq)]
+        {
+            if let Some(num_str) = query.strip_prefix(op_str)
+                && let Ok(num) = num_str.trim().parse::<f64>()
+            {
+                return Ok(Self::Numeric(op, num));
+            };
+        };
+        if query.len() >= 2 && query.starts_with('/') && query.ends_with('/') {
+            return Ok(Self::Regex(regex::Regex::new(&query[1..query.len() - 1])?));
+        };
+        Ok(Self::Substring(query.to_lowercase()))
+    }
+
+    pub fn is_match_str(&self, s: &str) -> bool {
+        match self {
+            Self::Substring(sub) => !sub.is_empty() && s.to_lowercase().contains(sub),
+            Self::Regex(re) => re.is_match(s),
+            Self::Numeric(op, num) => {
+                match s.trim().parse::<f64>() {
+                    Ok(val) => match op {
+                        NumericOp::Gt => val > *num,
+                        NumericOp::Ge => val >= *num,
+                        NumericOp::Lt => val < *num,
+                        NumericOp::Le => val <= *num,
+                        NumericOp::Eq => val == *num,
+                        NumericOp::Ne => val != *num,
+                    },
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+
+    /// Numeric searches only apply to values.
+    pub fn is_match_node(&self, scope: &SaveTreeSearchScope, name: &str, text: &str, path: &str) -> bool {
+        if let Self::Numeric(..) = self {
+            return scope.values && self.is_match_str(text);
+        };
+        (scope.names && self.is_match_str(name))
+            || (scope.values && self.is_match_str(text))
+            || (scope.paths && self.is_match_str(path))
+    }
+
+    /// All matching element nodes below root (inclusive), in document order.
+    /// Searches the xml tree rather than the SaveNodeTree, so branches that haven't been expanded are included.
+    pub fn search(&self, scope: &SaveTreeSearchScope, root: xot::Node, xtree: &xot::Xot) -> Vec<SaveTreeSearchResult> {
+        let mut results: Vec<SaveTreeSearchResult> = Vec::new();
+        let root_path = SaveDataManager::get_node_path_xt(xtree, root);
+        self.search_below(scope, root, &root_path, &mut Vec::new(), xtree, &mut results);
+        results
+    }
+
+    /// Matches node then the elements below it. path_parts are the names (and positions among same named siblings) 
+    /// from root down to node, only joined into a path for a match or a path search.
+    fn search_below<'a>(&self, scope: &SaveTreeSearchScope, node: xot::Node, root_path: &str, path_parts: &mut Vec<(&'a str, Option<usize>)>, 
+        xtree: &'a xot::Xot, results: &mut Vec<SaveTreeSearchResult>) 
+    {
+        let join_path = |path_parts: &[(&str, Option<usize>)]| {
+            let mut path = root_path.to_string();
+            for (name, position) in path_parts {
+                path.push('/');
+                path.push_str(name);
+                if let Some(position) = position {path.push_str(&format!("[{}]", position))};
+            };
+            path
+        };
+
+        // text nodes are shown under their element, only match the element
+        if xtree.is_element(node) {
+            let name = SaveNodeTree::get_name_from_node(&node, xtree).unwrap_or_default();
+            let text = SaveNodeTree::get_str_from_node(&node, xtree).unwrap_or_default();
+            let text = if text.trim().is_empty() {""} else {text};
+            let b_match = match scope.paths {
+                true => self.is_match_node(scope, name, text, &join_path(path_parts)),
+                false => self.is_match_node(scope, name, text, ""),
+            };
+            if b_match {
+                results.push(SaveTreeSearchResult { node, path: join_path(path_parts), text: text.to_string() });
+            };
+        };
+
+        let children: Vec<(xot::Node, &str)> = xtree.children(node)
+            .filter_map(|x| Some((x, SaveDataManager::get_name_from_node_xt(xtree, x)?)))
+            .collect();
+        let mut name_counts: HashMap<&str, usize> = HashMap::new();
+        for (_, name) in children.iter() {
+            *name_counts.entry(name).or_default() += 1;
+        };
+        let mut name_positions: HashMap<&str, usize> = HashMap::new();
+        for (child, name) in children {
+            let position = match name_counts[name] {
+                1 => None,
+                _ => {
+                    let position = name_positions.entry(name).or_default();
+                    *position += 1;
+                    Some(*position)
+                },
+            };
+            path_parts.push((name, position));
+            self.search_below(scope, child, root_path, path_parts, xtree, results);
+            path_parts.pop();
+        };
+    }
+}
+
+/// Nodes that stay visible when the tree is filtered to the search results, 
+/// the results, all their ancestors, and everything below them.
+pub fn get_visible_nodes(results: &[SaveTreeSearchResult], xtree: &xot::Xot) -> HashSet<xot::Node> {
+    let mut visible: HashSet<xot::Node> = HashSet::new();
+    for result in results.iter() {
+        visible.extend(xtree.ancestors(result.node));
+        visible.extend(xtree.descendants(result.node));
+    };
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEARCH_XML: &str = "<SaveGame><PlayerData><Brass>1200</Brass>\
+        <Tools><Tool><type>Axe</type><level>2</level></Tool><Tool><type>Hoe</type><level>5</level></Tool></Tools>\
+        </PlayerData></SaveGame>";
+
+    /// (path, text) of each search result in SEARCH_XML.
+    fn search(query: &str, scope: SaveTreeSearchScope) -> Vec<(String, String)> {
+        let mut xtree = xot::Xot::new();
+        let root = xtree.parse(SEARCH_XML).unwrap();
+        let doc_el = xtree.document_element(root).unwrap();
+        SaveTreeSearch::parse(query).unwrap().search(&scope, doc_el, &xtree).into_iter()
+            .map(|x| (x.path, x.text))
+            .collect()
+    }
+
+    #[test]
+    fn parse_query_kinds() {
+        assert!(matches!(SaveTreeSearch::parse(" >= 10 ").unwrap(), SaveTreeSearch::Numeric(NumericOp::Ge, x) if x == 10.0));
+        assert!(matches!(SaveTreeSearch::parse("!=0").unwrap(), SaveTreeSearch::Numeric(NumericOp::Ne, x) if x == 0.0));
+        assert!(matches!(SaveTreeSearch::parse("/^Ax/").unwrap(), SaveTreeSearch::Regex(_)));
+        // an operator without a number is a substring
+        assert!(matches!(SaveTreeSearch::parse("=Axe").unwrap(), SaveTreeSearch::Substring(x) if x == "=axe"));
+        assert!(matches!(SaveTreeSearch::parse("/").unwrap(), SaveTreeSearch::Substring(_)));
+        assert!(SaveTreeSearch::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn match_strings() {
+        assert!(SaveTreeSearch::parse("BRASS").unwrap().is_match_str("Brass"));
+        assert!(!SaveTreeSearch::parse("").unwrap().is_match_str("Brass"));
+        assert!(SaveTreeSearch::parse("/^[A-Z]x/").unwrap().is_match_str("Axe"));
+        assert!(!SaveTreeSearch::parse("/^ax/").unwrap().is_match_str("Axe"));
+        assert!(SaveTreeSearch::parse("<3").unwrap().is_match_str(" 2 "));
+        assert!(!SaveTreeSearch::parse("<3").unwrap().is_match_str("two"));
+    }
+
+    #[test]
+    fn search_names_values_and_paths() {
+        let scope = SaveTreeSearchScope::default();
+        assert_eq!(search("hoe", scope), vec![("SaveGame/PlayerData/Tools/Tool[2]/type".to_string(), "Hoe".to_string())]);
+        assert_eq!(search("brass", scope), vec![("SaveGame/PlayerData/Brass".to_string(), "1200".to_string())]);
+        // numeric searches only match values
+        let paths: Vec<String> = search(">1", scope).into_iter().map(|x| x.0).collect();
+        assert_eq!(paths, vec!["SaveGame/PlayerData/Brass", "SaveGame/PlayerData/Tools/Tool[1]/level", "SaveGame/PlayerData/Tools/Tool[2]/level"]);
+        assert!(search("tool[2]", scope).is_empty());
+        let path_scope = SaveTreeSearchScope { names: false, values: false, paths: true };
+        assert_eq!(search("tool[2]/", path_scope).len(), 2);
+    }
+}
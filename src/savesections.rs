@@ -0,0 +1,1990 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::hash::{Hash, Hasher};
+
+use crate::apothrecipes::ApothRecipeManager;
+use crate::app::cure_status_badge_ui;
+use crate::config::AppConfig;
+use crate::lootitems::LootManager;
+use crate::refdata::{ApothRecipeKey, CalendarInfo, FarmInfo, GameDate, KnownListInfo, KnownListSource, RefDataManager, ToolXpTable, empty_table_note};
+use crate::savechanges::{SaveChangeEvent, SaveChangeListener};
+use crate::savedata::{LocationItemRef, SaveDataManager, SaveInventoryItemRef, bool_text_like};
+use crate::savesurvey::{NodeShape, SaveSurvey, SurveyCategory, name_words};
+use crate::savemodel::{ItemStack, PlayerDataNodes, SaveModelError, Stat, StatValue, ToolLevel, write_node_if_changed};
+
+#[derive(Debug, Clone)]
+pub struct SaveSectionError(String);
+
+impl std::fmt::Display for SaveSectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SaveSectionError {}
+
+/// Reference data sections read alongside the save.
+pub struct SectionContext<'a> {
+    pub appconfig: &'a AppConfig,
+    pub lm: &'a LootManager,
+    pub arm: &'a ApothRecipeManager,
+    pub rd: &'a RefDataManager,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SectionPlacement {
+    CentralPanel,
+    PlayerDataWindow,
+    /// a window of its own, opened from the top panel
+    Window,
+}
+
+/// # SaveSection
+/// An editor for one part of the save: finds its nodes, reads them into a typed model, shows the model,
+/// and writes it back. Sections are kept in a SaveSectionRegistry and updated from SaveChanges like the other views.
+pub trait SaveSection: SaveChangeListener {
+    fn name(&self) -> &'static str;
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::PlayerDataWindow
+    }
+
+    /// Finds the section's nodes in a loaded save, an error fails loading the save.
+    fn locate(&mut self, sm: &SaveDataManager, sc: &SectionContext) -> Result<(), Box<dyn Error>>;
+
+    /// Reads the model from the located nodes.
+    fn load(&mut self, sm: &SaveDataManager, lm: &LootManager) -> Result<(), Box<dyn Error>>;
+
+    /// Nodes the section keeps references to, pinned so structure edits can't remove them.
+    fn tracked_nodes(&self) -> Vec<xot::Node>;
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String);
+
+    /// Extra entries for the Inventory menu.
+    fn menu_ui(&mut self, _ui: &mut egui::Ui, _sm: &mut SaveDataManager, _sc: &SectionContext) {}
+
+    /// Problems with the model, nothing is written back while there are any.
+    fn validate(&self, sc: &SectionContext) -> Vec<String>;
+
+    /// Writes model values that differ from the save, returns a warning if a value was adjusted on the way.
+    fn write_back(&mut self, sm: &mut SaveDataManager, sc: &SectionContext) -> Result<(), String>;
+
+    fn clear(&mut self);
+}
+
+/// Validates and writes back section, problems and warnings go to error_msg.
+pub fn validate_and_write_back(section: &mut dyn SaveSection, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+    let problems = section.validate(sc);
+    if !problems.is_empty() {
+        *error_msg = format!("{}: {}", section.name(), problems.join("; "));
+        return;
+    };
+    if let Err(s) = section.write_back(sm, sc) {
+        *error_msg = s;
+    };
+}
+
+/// # SaveSectionRegistry
+/// The sections the app shows, loaded in registration order after SaveDataManager::load_data.
+pub struct SaveSectionRegistry {
+    sections: Vec<Box<dyn SaveSection>>,
+}
+
+impl Default for SaveSectionRegistry {
+    fn default() -> Self {
+        let mut registry = Self { sections: Vec::new() };
+        registry.register(Box::new(InventorySection::default()));
+        registry.register(Box::new(BrassStatsSection::default()));
+        registry.register(Box::new(ToolLevellingSection::default()));
+        registry.register(Box::new(TimeSection::default()));
+        registry.register(Box::new(RelationshipsSection::default()));
+        registry.register(Box::new(FarmSection::default()));
+        registry.register(Box::new(KnownRecipesSection::default()));
+        registry
+    }
+}
+
+impl SaveSectionRegistry {
+
+    pub fn register(&mut self, section: Box<dyn SaveSection>) {
+        self.sections.push(section);
+    }
+
+    pub fn clear_data(&mut self) {
+        self.sections.iter_mut().for_each(|x| x.clear());
+    }
+
+    /// Locates and loads every section, the first error names the section it came from.
+    pub fn load_data(&mut self, sm: &mut SaveDataManager, sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        for section in self.sections.iter_mut() {
+            let name = section.name();
+            section.locate(sm, sc)
+                .and_then(|_| section.load(sm, sc.lm))
+                .map_err(|e| Box::new(SaveSectionError(format!("{}: {}", name, e))))?;
+            section.tracked_nodes().into_iter().for_each(|x| sm.changes.pin(x));
+        };
+        Ok(())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn SaveSection>> {
+        self.sections.iter_mut()
+    }
+
+    pub fn iter_placed_mut(&mut self, placement: SectionPlacement) -> impl Iterator<Item = &mut Box<dyn SaveSection>> {
+        self.sections.iter_mut().filter(move |x| x.placement() == placement)
+    }
+
+    /// Problems from every section, prefixed with the section name.
+    pub fn validate(&self, sc: &SectionContext) -> Vec<String> {
+        self.sections.iter()
+            .flat_map(|section| section.validate(sc).into_iter().map(|x| format!("{}: {}", section.name(), x)))
+            .collect()
+    }
+}
+
+impl SaveChangeListener for SaveSectionRegistry {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        self.sections.iter_mut().for_each(|x| x.on_save_changes(sm, lm, events));
+    }
+}
+
+/// Text changed on one of nodes, or the structure changed anywhere.
+fn events_touch(events: &[SaveChangeEvent], nodes: &[xot::Node]) -> bool {
+    events.iter().any(|x| match x {
+        SaveChangeEvent::TextChanged(node) => nodes.contains(node),
+        _ => x.is_structural(),
+    })
+}
+
+
+#[derive(Debug)]
+pub struct SaveInventoryItemCount {
+    pub count: i32,
+}
+
+impl SaveInventoryItemCount {
+
+    pub fn new(count: i32) -> Self {
+        Self {
+            count,
+        }
+    }
+
+}
+
+pub struct AppSaveInventoryItem {
+    pub save_item_ref: SaveInventoryItemRef,
+    pub uid: i32,
+    pub counts: [SaveInventoryItemCount; 5],
+    pub name: String,
+    pub pickup_type_name: String,
+    pub cost: i32,
+    // sprite_idx: i32
+}
+
+impl Hash for AppSaveInventoryItem {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.save_item_ref.key_int_node.hash(state);
+    }
+}
+
+impl AppSaveInventoryItem {
+
+    pub fn new(save_item_ref: &SaveInventoryItemRef, sm: &SaveDataManager, lm: &LootManager) -> Self {
+        let sir = save_item_ref.clone();
+        let uid = save_item_ref.get_uid(sm);
+        let counts = save_item_ref.get_counts(sm).map(SaveInventoryItemCount::new);
+        let li = save_item_ref.get_lootitem_ref(sm,lm);
+        let li_name = li.name.to_string();
+        let li_pickup_type = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
+
+        Self {
+            save_item_ref: sir,
+            uid,
+            counts,
+            name: li_name,
+            pickup_type_name: li_pickup_type,
+            cost: li.cost,
+            // sprite_idx: li.sprite_idx
+        }
+    }
+
+    pub fn update_fromref(&mut self, sm: &SaveDataManager, lm: &LootManager) {
+        let sir = &self.save_item_ref;
+        self.uid = sir.get_uid(sm);
+        self.counts = sir.get_counts(sm).map(SaveInventoryItemCount::new);
+        let li = sir.get_lootitem_ref(sm,lm);
+        self.name = li.name.to_string();
+        self.pickup_type_name = lm.pickup_type_lookup_rev[&li.type_of_pickup].to_string();
+        self.cost = li.cost;
+    }
+}
+
+/// # InventorySection
+/// PlayerData/Inventory/AllItems, one row per item.
+/// The item refs stay on SaveDataManager as larder and shop items share copy, remove and the bulk qty edits.
+#[derive(Default)]
+pub struct InventorySection {
+    inventory_node: Option<xot::Node>,
+    allitems_node: Option<xot::Node>,
+    pub items: Vec<AppSaveInventoryItem>,
+    /// cells edited since the last write back, (row, count index or None for the uid)
+    edited: Vec<(usize, Option<usize>)>,
+}
+
+impl InventorySection {
+
+    pub fn sort_by_type_name(&mut self) {
+        self.items.sort_by(|a,b|
+            {let first = a.pickup_type_name.cmp(&b.pickup_type_name);
+            let second = a.name.cmp(&b.name);
+            first.then(second)}
+        );
+    }
+}
+
+impl SaveSection for InventorySection {
+
+    fn name(&self) -> &'static str {
+        "Inventory"
+    }
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::CentralPanel
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, _sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        self.inventory_node = sm.inventory_node;
+        self.allitems_node = sm.allitems_node;
+        match self.allitems_node {
+            Some(_) => Ok(()),
+            None => Err(Box::new(SaveSectionError("no PlayerData/Inventory/AllItems".to_string()))),
+        }
+    }
+
+    fn load(&mut self, sm: &SaveDataManager, lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        // rows read the uid and counts without checks
+        let mut problems: Vec<String> = Vec::new();
+        for item_ref in sm.save_inventory_ref.iter() {
+            match ItemStack::read(&sm.xtree, item_ref) {
+                Ok(x) if !lm.full_item_lookup.contains_key(&x.uid) => problems.push(format!("unknown uid {}", x.uid)),
+                Ok(_) => {},
+                Err(e) => problems.extend(e),
+            };
+        };
+        if !problems.is_empty() {return Err(Box::new(SaveModelError { problems }))};
+        self.items = sm.save_inventory_ref.iter()
+            .map(|x| AppSaveInventoryItem::new(x, sm, lm))
+            .collect();
+        self.sort_by_type_name();
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        [self.inventory_node, self.allitems_node].into_iter().flatten().collect()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        use egui_extras::{Column, TableBuilder};
+
+        let table = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(false)
+            .cell_layout(egui::Layout::centered_and_justified(egui::Direction::TopDown))
+            .column(Column::initial(40.0).at_least(40.0))
+            .column(Column::initial(65.0).at_least(65.0))
+            .column(Column::initial(65.0).at_least(65.0))
+            .column(Column::initial(65.0).at_least(65.0))
+            .column(Column::initial(65.0).at_least(65.0))
+            .column(Column::initial(65.0).at_least(65.0))
+            .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
+            .column(Column::initial(160.0).range(40.0..=200.0).resizable(true))
+            .column(Column::initial(40.0).at_least(40.0))
+            .column(Column::initial(80.0).at_least(40.0))
+            .column(Column::initial(20.0).at_least(20.0))
+            .column(Column::initial(20.0).at_least(20.0))
+            .min_scrolled_height(0.0);
+
+        let mut b_changed = false;
+        table
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.strong("uid");
+                });
+                for star in 1..=5 {
+                    header.col(|ui| {
+                        ui.strong(format!("Qty, Star {}", star));
+                    });
+                };
+                header.col(|ui| {
+                    ui.strong("Name");
+                });
+                header.col(|ui| {
+                    ui.strong("Type");
+                });
+                header.col(|ui| {
+                    ui.strong("Cost");
+                });
+                header.col(|ui| {
+                    ui.strong("Cure");
+                });
+                header.col(|ui| {
+                    ui.strong("");
+                });
+                header.col(|ui| {
+                    ui.strong("");
+                });
+            })
+            .body(|body| {
+                let row_height = 30.0;
+                let num_rows = self.items.len();
+                body.rows(row_height, num_rows, |mut row| {
+                    let row_index = row.index();
+                    row.col(|ui| {
+                        if ui.add(egui::DragValue::new(&mut self.items[row_index].uid)).changed() {
+                            self.edited.push((row_index, None));
+                            b_changed = true;
+                        };
+                    });
+                    for count_index in 0..5 {
+                        row.col(|ui| {
+                            if ui.add(egui::DragValue::new(&mut self.items[row_index].counts[count_index].count)).changed() {
+                                self.edited.push((row_index, Some(count_index)));
+                                b_changed = true;
+                            };
+                        });
+                    };
+                    row.col(|ui| {
+                        ui.label(self.items[row_index].name.clone());
+                    });
+                    row.col(|ui| {
+                        ui.label(self.items[row_index].pickup_type_name.clone());
+                    });
+                    row.col(|ui| {
+                        ui.label(self.items[row_index].cost.to_string());
+                    });
+                    row.col(|ui| {
+                        let uid = self.items[row_index].uid;
+                        if let Some(cure_status) = sc.arm.get_cure_status(uid) {
+                            let recipes_text: Vec<String> = sc.arm.get_recipes_for_item(uid).iter()
+                                .map(|x| format!("{} ({:?})", sc.arm.get_ailment_name(x.ailment_id), x.result_type))
+                                .collect();
+                            cure_status_badge_ui(ui, cure_status).on_hover_text(recipes_text.join("\n"));
+                        };
+                    });
+                    row.col(|ui| {
+                        if ui.add_sized([20.0, 20.0], egui::Button::new("+")).clicked() {
+                            // the new row is added when the change is dispatched
+                            let result_copy_new = self.items[row_index].save_item_ref.copy_new(sm, LocationItemRef::Inventory);
+                            match result_copy_new {
+                                Ok(_) => {},
+                                Err(_e) => {
+                                    error_msg.push_str("Error unable to copy to new inventory item.");
+                                }
+                            }
+                        };
+                    });
+                    row.col(|ui| {
+                        if ui.add_sized([20.0, 20.0], egui::Button::new("-")).clicked() {
+                            let result_remove = self.items[row_index].save_item_ref.remove(sm, LocationItemRef::Inventory);
+                            match result_remove {
+                                Ok(_) => {},
+                                Err(_e) => {
+                                    error_msg.push_str("Error unable to remove inventory item.");
+                                }
+                            }
+                        };
+                    });
+                })
+            });
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn menu_ui(&mut self, ui: &mut egui::Ui, _sm: &mut SaveDataManager, _sc: &SectionContext) {
+        if ui.button("Sort by type, name").clicked() {
+            self.sort_by_type_name();
+            ui.close_menu();
+        };
+        if ui.button("Sort by cost, name").clicked() {
+            self.items.sort_by(|a,b|
+                {let first = b.cost.cmp(&a.cost);
+                let second = a.name.cmp(&b.name);
+                first.then(second)}
+            );
+            ui.close_menu();
+        };
+        if ui.button("Sort by UID").clicked() {
+            self.items.sort_by(|a,b|
+                {a.uid.cmp(&b.uid)}
+            );
+            ui.close_menu();
+        };
+        if ui.button("Sort by name").clicked() {
+            self.items.sort_by(|a,b|
+                {a.name.cmp(&b.name)}
+            );
+            ui.close_menu();
+        };
+    }
+
+    fn validate(&self, sc: &SectionContext) -> Vec<String> {
+        self.items.iter()
+            .filter(|x| !sc.lm.full_item_lookup.contains_key(&x.uid))
+            .map(|x| format!("unknown uid {}", x.uid))
+            .collect()
+    }
+
+    /// Writes only the cells edited in the table, so values changed elsewhere in the meantime aren't reverted.
+    fn write_back(&mut self, sm: &mut SaveDataManager, sc: &SectionContext) -> Result<(), String> {
+        let mut warning: Option<String> = None;
+        for (row_index, count_index) in std::mem::take(&mut self.edited) {
+            let Some(item) = self.items.get_mut(row_index) else {continue};
+            match count_index {
+                None => {
+                    if item.save_item_ref.get_uid(sm) != item.uid {
+                        item.uid = item.save_item_ref.set_uid(sm, item.uid);
+                    };
+                },
+                Some(count_index) => {
+                    let (new_count, b_cure_guard_hit) = item.save_item_ref.set_count_at_idx(
+                        count_index, item.counts[count_index].count, sm, Some(sc.lm), Some(sc.arm), sc.appconfig.cure_quality_guard);
+                    item.counts[count_index].count = new_count;
+                    if b_cure_guard_hit {
+                        warning = Some(format!("Warning: {} is not a full cure, qty set to {}.", item.name, new_count));
+                    };
+                },
+            };
+        };
+        match warning {
+            Some(s) => Err(s),
+            None => Ok(()),
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for InventorySection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if events.iter().any(|x| x.is_structural()) {
+            // rows move, edits not written yet (e.g. an unknown uid) are dropped
+            self.edited.clear();
+            // sm.save_inventory_ref is already looked up again
+            self.items.retain_mut(|asii| match sm.save_inventory_ref.iter().find(|x| x.item_node == asii.save_item_ref.item_node) {
+                Some(x) => {asii.save_item_ref = x.clone(); true},
+                None => false,
+            });
+            for si in sm.save_inventory_ref.iter() {
+                if self.items.iter().any(|x| x.save_item_ref.item_node == si.item_node) {continue};
+                // after the item it was copied or pasted next to, if there is one
+                let insert_idx = sm.xtree.preceding_siblings(si.item_node)
+                    .find_map(|node| self.items.iter().position(|x| x.save_item_ref.item_node == node))
+                    .map_or(self.items.len(), |x| x + 1);
+                self.items.insert(insert_idx, AppSaveInventoryItem::new(si, sm, lm));
+            };
+            self.items.iter_mut().for_each(|x| x.update_fromref(sm, lm));
+            return;
+        };
+
+        for asii in self.items.iter_mut() {
+            let sir = &asii.save_item_ref;
+            let item_nodes: Vec<xot::Node> = std::iter::once(sir.key_int_node).chain(sir.count_int_nodes.iter().copied()).collect();
+            if events_touch(events, &item_nodes) {
+                asii.update_fromref(sm, lm);
+            };
+        };
+    }
+}
+
+
+/// # BrassStatsSection
+/// PlayerData/BrassCount and the stats in PlayerData/characterStats.
+/// Stat names, ranges and descriptions come from the stats table in saveedit_data.
+#[derive(Default)]
+pub struct BrassStatsSection {
+    nodes: PlayerDataNodes,
+    brass: u32,
+    /// same order as nodes.stats_nodes
+    stats: Vec<Stat>,
+}
+
+impl SaveSection for BrassStatsSection {
+
+    fn name(&self) -> &'static str {
+        "Brass and stats"
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, _sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        self.nodes.locate_brass_stats(sm)?;
+        Ok(())
+    }
+
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        let brass = self.nodes.read_brass(&sm.xtree);
+        let stats = self.nodes.read_stats(&sm.xtree);
+        let problems: Vec<String> = brass.clone().err().into_iter().chain(stats.clone().err().unwrap_or_default()).collect();
+        if !problems.is_empty() {return Err(Box::new(SaveModelError { problems }))};
+        self.brass = brass.unwrap_or_default();
+        self.stats = stats.unwrap_or_default();
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        [self.nodes.brass_count_node, self.nodes.character_stats_node].into_iter()
+            .flatten()
+            .chain(self.nodes.stats_nodes.iter().copied())
+            .collect()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        let mut b_changed = false;
+        ui.horizontal(|contents| {
+            contents.columns(2, |columns| {
+                columns[0].add(egui::Label::new("Brass"));
+                b_changed |= columns[1].add(egui::DragValue::new(&mut self.brass)).changed();
+            });
+        });
+
+        ui.add(egui::Label::new("Player stats"));
+        if sc.rd.stat_infos.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "stats", &sc.appconfig.filename_saveedit_stats_info));
+        };
+        for stat in self.stats.iter_mut() {
+            let stat_info = sc.rd.get_stat_info(&stat.name);
+            ui.horizontal(|contents| {
+                contents.columns(2, |columns| {
+                    let display_name = stat_info.map(|x| x.display_name.as_str()).filter(|x| !x.is_empty()).unwrap_or(&stat.name);
+                    let hover_text = match stat_info {
+                        Some(info) => format!("{}\n{}, {} to {}", info.description, stat.name, info.min, info.max),
+                        None => stat.name.clone(),
+                    };
+                    columns[0].add(egui::Label::new(display_name)).on_hover_text(hover_text);
+
+                    // a save value already out of range is shown as is until edited
+                    columns[1].horizontal(|contents| {
+                        b_changed |= match (&mut stat.value, stat_info) {
+                            (StatValue::Integer(x), Some(info)) => contents.add(egui::DragValue::new(x)
+                                .range(info.min.ceil() as i64..=info.max.floor() as i64).clamp_existing_to_range(false)).changed(),
+                            (StatValue::Integer(x), None) => contents.add(egui::DragValue::new(x)).changed(),
+                            (StatValue::Decimal(x), Some(info)) => contents.add(egui::DragValue::new(x).speed(0.1)
+                                .range(info.min..=info.max).clamp_existing_to_range(false)).changed(),
+                            (StatValue::Decimal(x), None) => contents.add(egui::DragValue::new(x).speed(0.1)).changed(),
+                        };
+                        if let Some(info) = stat_info && !(info.min..=info.max).contains(&stat.value.as_f64()) {
+                            let warn_color = contents.visuals().warn_fg_color;
+                            contents.colored_label(warn_color, "Out of range")
+                                .on_hover_text(format!("{} is outside {} to {}", stat.value, info.min, info.max));
+                        };
+                    });
+                });
+            });
+        };
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        self.stats.iter()
+            .filter(|x| !x.value.as_f64().is_finite())
+            .map(|x| format!("{} {} is not a number", x.name, x.value))
+            .collect()
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        if let Some(brass_count_node) = self.nodes.brass_count_node {
+            write_node_if_changed(sm, brass_count_node, &self.brass);
+        };
+        for (node, stat) in self.nodes.stats_nodes.iter().zip(self.stats.iter()) {
+            write_node_if_changed(sm, *node, &stat.value);
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for BrassStatsSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if self.nodes.brass_count_node.is_none() || !events_touch(events, &self.tracked_nodes()) {return};
+        // values that don't parse keep the last model shown
+        let _ = self.load(sm, lm);
+    }
+}
+
+
+/// # ToolLevellingSection
+/// PlayerData/ToolLevelling, level and xp per tool.
+/// Level and xp are kept consistent using the thresholds from the tool levels table in saveedit_data,
+/// tools the table doesn't list are edited as plain numbers.
+#[derive(Default)]
+pub struct ToolLevellingSection {
+    nodes: PlayerDataNodes,
+    /// same order as nodes.tool_level_ref
+    tools: Vec<ToolLevel>,
+}
+
+impl SaveSection for ToolLevellingSection {
+
+    fn name(&self) -> &'static str {
+        "Tool levelling"
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, _sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        self.nodes.locate_tool_levels(sm)?;
+        Ok(())
+    }
+
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        self.tools = self.nodes.read_tools(&sm.xtree).map_err(|problems| Box::new(SaveModelError { problems }))?;
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        self.nodes.tool_levelling_node.into_iter()
+            .chain(self.nodes.tool_level_ref.iter().flat_map(|x| [x.tool_node, x.tool_type_node, x.tool_level_node, x.tool_current_xp_node]))
+            .collect()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        let mut b_changed = false;
+        if sc.rd.tool_xp_tables.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "tool levels", &sc.appconfig.filename_saveedit_tool_levels));
+        };
+        let b_any_table = self.tools.iter().any(|x| sc.rd.get_tool_xp_table(&x.tool_type).is_some());
+        if ui.add_enabled(b_any_table, egui::Button::new("Max all tools")).on_disabled_hover_text("No tool has thresholds in the tool levels table").clicked() {
+            for tool in self.tools.iter_mut() {
+                if let Some(table) = sc.rd.get_tool_xp_table(&tool.tool_type) {
+                    max_tool(tool, table);
+                    b_changed = true;
+                };
+            };
+        };
+        ui.horizontal(|contents| {
+            contents.columns(5, |columns| {
+                columns[0].label("Tool");
+                columns[1].label("Level");
+                columns[2].label("XP");
+                columns[3].label("Next level");
+            });
+        });
+        for tool in self.tools.iter_mut() {
+            let table = sc.rd.get_tool_xp_table(&tool.tool_type);
+            ui.horizontal(|contents| {
+                contents.columns(5, |columns| {
+                    columns[0].add(egui::Label::new(tool.tool_type.clone()));
+
+                    // keep level and xp inside the same band of the thresholds table when either is edited
+                    let mut level_drag = egui::DragValue::new(&mut tool.level);
+                    if let Some(table) = table {
+                        // a save already past the table keeps its level until edited
+                        level_drag = level_drag.range(table.min_level()..=table.max_level()).clamp_existing_to_range(false);
+                    };
+                    if columns[1].add(level_drag).changed() {
+                        if let Some(table) = table {
+                            tool.current_xp = table.clamp_xp_to_level(tool.level, tool.current_xp);
+                        };
+                        b_changed = true;
+                    };
+                    if columns[2].add(egui::DragValue::new(&mut tool.current_xp).range(0.0..=f32::MAX).clamp_existing_to_range(false)).changed() {
+                        if let Some(table) = table {
+                            tool.level = table.level_for_xp(tool.current_xp);
+                        };
+                        b_changed = true;
+                    };
+
+                    match table {
+                        Some(table) if !table.is_consistent(tool.level, tool.current_xp) => {
+                            let warn_color = columns[3].visuals().warn_fg_color;
+                            columns[3].colored_label(warn_color, "Level and XP disagree")
+                                .on_hover_text(format!("{} XP is level {} in the tool levels table", tool.current_xp, table.level_for_xp(tool.current_xp)));
+                        },
+                        Some(table) => match table.progress(tool.level, tool.current_xp) {
+                            Some((done, span)) => {
+                                columns[3].add(egui::ProgressBar::new(done / span).text(format!("{:.0} / {:.0}", done, span)));
+                            },
+                            None => {columns[3].label("Max level");},
+                        },
+                        None => {columns[3].label("No thresholds");},
+                    };
+
+                    if columns[4].add_enabled(table.is_some(), egui::Button::new("Max")).clicked() {
+                        if let Some(table) = table {
+                            max_tool(tool, table);
+                        };
+                        b_changed = true;
+                    };
+                });
+            });
+        };
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        self.tools.iter()
+            .filter(|x| !x.current_xp.is_finite() || x.current_xp < 0.0)
+            .map(|x| format!("{} XP {} is negative", x.tool_type, x.current_xp))
+            .collect()
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        for (tool_ref, tool) in self.nodes.tool_level_ref.iter().zip(self.tools.iter()) {
+            write_node_if_changed(sm, tool_ref.tool_level_node, &tool.level);
+            write_node_if_changed(sm, tool_ref.tool_current_xp_node, &tool.current_xp);
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Top level of the table, keeping any XP already past its threshold.
+fn max_tool(tool: &mut ToolLevel, table: &ToolXpTable) {
+    tool.level = table.max_level();
+    tool.current_xp = tool.current_xp.max(table.threshold(tool.level).unwrap_or(0.0));
+}
+
+impl SaveChangeListener for ToolLevellingSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if self.nodes.tool_levelling_node.is_none() || !events_touch(events, &self.tracked_nodes()) {return};
+        let _ = self.load(sm, lm);
+    }
+}
+
+
+/// How the save stores the season.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum SeasonFormat {
+    #[default]
+    Index,
+    Name,
+}
+
+/// # TimeSection
+/// The in-game date and the character's age, found using the paths in the calendar table in saveedit_data.
+/// Date edits move the total days counter and the age along with them, so the save stays consistent.
+/// The date isn't editable when the save has a total days counter but the calendar table doesn't give the season lengths.
+#[derive(Default)]
+pub struct TimeSection {
+    calendar: CalendarInfo,
+    day_node: Option<xot::Node>,
+    season_node: Option<xot::Node>,
+    year_node: Option<xot::Node>,
+    total_days_node: Option<xot::Node>,
+    age_node: Option<xot::Node>,
+    season_format: SeasonFormat,
+    date: GameDate,
+    total_days: Option<i64>,
+    age: Option<StatValue>,
+    b_age_fixed: bool,
+    /// paths matching more than one node or not a single value, those aren't edited
+    path_problems: Vec<String>,
+    /// values found that don't parse, those nodes are left alone
+    problems: Vec<String>,
+}
+
+impl TimeSection {
+
+    /// The single value the path matches, None if the path isn't set or matches nothing.
+    /// A path matching more than one node, or an element with child elements, is reported and not edited.
+    fn find_value_node(sm: &SaveDataManager, path: Option<&str>, problems: &mut Vec<String>) -> Option<xot::Node> {
+        let path = path?;
+        let nodes = match sm.query(path) {
+            Ok(x) => x,
+            Err(e) => {
+                problems.push(format!("{}: {}", path, e));
+                return None;
+            },
+        };
+        match nodes[..] {
+            [] => None,
+            [node] if !sm.xtree.children(node).any(|x| sm.xtree.is_element(x)) => Some(node),
+            [_] => {
+                problems.push(format!("{} is not a single value", path));
+                None
+            },
+            _ => {
+                problems.push(format!("{} matches {} nodes", path, nodes.len()));
+                None
+            },
+        }
+    }
+
+    fn locate_nodes(&mut self, sm: &SaveDataManager) {
+        self.path_problems.clear();
+        let problems = &mut self.path_problems;
+        self.day_node = Self::find_value_node(sm, self.calendar.day_path.as_deref(), problems);
+        self.season_node = Self::find_value_node(sm, self.calendar.season_path.as_deref(), problems);
+        self.year_node = Self::find_value_node(sm, self.calendar.year_path.as_deref(), problems);
+        self.total_days_node = Self::find_value_node(sm, self.calendar.total_days_path.as_deref(), problems);
+        self.age_node = Self::find_value_node(sm, self.calendar.age_path.as_deref(), problems);
+    }
+
+    fn b_has_date(&self) -> bool {
+        self.day_node.is_some() || self.season_node.is_some() || self.year_node.is_some() || self.total_days_node.is_some()
+    }
+
+    /// Day arithmetic needs the season lengths from the calendar table.
+    fn b_can_shift(&self) -> bool {
+        self.calendar.days_per_year().is_some()
+    }
+
+    /// A save with a total days counter can only have its date edited if the counter can be moved with it.
+    fn b_date_editable(&self) -> bool {
+        self.b_can_shift() || self.total_days_node.is_none()
+    }
+
+    /// The date as far as the save and the calendar table give it.
+    fn date_text(&self) -> String {
+        if self.b_can_shift() || (self.day_node.is_some() && self.season_node.is_some() && self.year_node.is_some()) {
+            return self.calendar.format_date(&self.date);
+        };
+        let mut parts: Vec<String> = Vec::new();
+        if self.day_node.is_some() {parts.push(format!("Day {}", self.date.day))};
+        if self.season_node.is_some() {parts.push(self.calendar.season_name(self.date.season))};
+        if self.year_node.is_some() {parts.push(format!("year {}", self.date.year))};
+        if let Some(total_days) = self.total_days {parts.push(format!("{} days in total", total_days))};
+        parts.join(", ")
+    }
+
+    /// Moves to date, shifting the total days counter and age by the same amount of time.
+    fn shift_to(&mut self, date: GameDate) {
+        let day_delta = self.calendar.day_number(&date).zip(self.calendar.day_number(&self.date)).map(|(x, y)| x - y);
+        let year_delta = date.year - self.date.year;
+        self.date = date;
+        if let (Some(total_days), Some(day_delta)) = (self.total_days.as_mut(), day_delta) {*total_days += day_delta};
+        if self.b_age_fixed || year_delta == 0 {return};
+        let age_delta = year_delta as f64 * self.calendar.age_per_year;
+        self.age = match self.age {
+            Some(StatValue::Integer(x)) => Some(StatValue::Integer((x + age_delta.round() as i64).max(0))),
+            Some(StatValue::Decimal(x)) => Some(StatValue::Decimal((x + age_delta).max(0.0))),
+            None => None,
+        };
+    }
+
+    fn shift_by_days(&mut self, days: i64) {
+        let Some(date) = self.calendar.day_number(&self.date).and_then(|x| self.calendar.date_from_day_number(x + days)) else {return};
+        self.shift_to(date);
+    }
+}
+
+impl SaveSection for TimeSection {
+
+    fn name(&self) -> &'static str {
+        "Time"
+    }
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::Window
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        // not every save or game version has every value, missing ones aren't edited
+        self.calendar = sc.rd.calendar.clone();
+        self.locate_nodes(sm);
+        Ok(())
+    }
+
+    /// Values that don't parse are reported in the window rather than failing the save,
+    /// a path set for another game version may point at something else.
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        self.problems.clear();
+        let problems = &mut self.problems;
+        let season_names = &self.calendar.season_names;
+        let mut parse_or_drop = |node: &mut Option<xot::Node>, what: &str, b_valid: &dyn Fn(&str) -> bool| -> Option<String> {
+            let text = sm.xtree.text_content_str((*node)?).unwrap_or_default().trim().to_string();
+            if !b_valid(&text) {
+                problems.push(format!("{} {:?} is not a {}", SaveDataManager::get_node_path_xt(&sm.xtree, (*node)?), text, what));
+                *node = None;
+                return None;
+            };
+            Some(text)
+        };
+        let day_text = parse_or_drop(&mut self.day_node, "day", &|x| x.parse::<u32>().is_ok());
+        let season_text = parse_or_drop(&mut self.season_node, "season",
+            &|x| x.parse::<i64>().is_ok() || season_names.iter().any(|name| name.eq_ignore_ascii_case(x)));
+        let year_text = parse_or_drop(&mut self.year_node, "year", &|x| x.parse::<i64>().is_ok());
+        let total_days_text = parse_or_drop(&mut self.total_days_node, "day count", &|x| x.parse::<i64>().is_ok());
+        let age_text = parse_or_drop(&mut self.age_node, "number", &|x| x.parse::<StatValue>().is_ok());
+
+        self.total_days = total_days_text.and_then(|x| x.parse::<i64>().ok());
+        // values the save doesn't have come from the total days counter, if the calendar table allows it
+        let mut date = self.total_days
+            .and_then(|x| self.calendar.date_from_day_number(x - self.calendar.total_days_base))
+            .unwrap_or(GameDate { day: self.calendar.first_day, season: 0, year: self.calendar.first_year });
+        if let Some(x) = day_text.and_then(|x| x.parse::<u32>().ok()) {date.day = x};
+        if let Some(text) = season_text {
+            if let Ok(x) = text.parse::<i64>() {
+                self.season_format = SeasonFormat::Index;
+                date.season = (x - self.calendar.season_index_base).max(0) as usize;
+            } else if let Some(idx) = self.calendar.season_names.iter().position(|x| x.eq_ignore_ascii_case(&text)) {
+                self.season_format = SeasonFormat::Name;
+                date.season = idx;
+            };
+        };
+        if let Some(x) = year_text.and_then(|x| x.parse::<i64>().ok()) {date.year = x};
+        self.date = date;
+        self.age = age_text.and_then(|x| x.parse::<StatValue>().ok());
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        [self.day_node, self.season_node, self.year_node, self.total_days_node, self.age_node].into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        if sc.rd.calendar.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "calendar", &sc.appconfig.filename_saveedit_calendar));
+        };
+        if !self.b_has_date() && self.age_node.is_none() {
+            ui.label("No date or age found in this save, set the paths in the calendar table (see README).");
+            for problem in self.path_problems.iter() {
+                ui.colored_label(ui.visuals().warn_fg_color, problem).on_hover_text("Not edited here");
+            };
+            return;
+        };
+
+        let mut b_changed = false;
+        for problem in self.path_problems.iter().chain(self.problems.iter()) {
+            ui.colored_label(ui.visuals().warn_fg_color, problem).on_hover_text("Not edited here");
+        };
+        if self.b_has_date() {
+            ui.strong(self.date_text());
+        };
+        if let Some(age) = self.age {
+            ui.label(format!("Age {}", age));
+        };
+        ui.separator();
+
+        let mut date = self.date;
+        let b_date_editable = self.b_date_editable();
+        egui::Grid::new("time_grid").num_columns(2).show(ui, |ui| {
+            if self.day_node.is_some() && b_date_editable {
+                let last_day = self.calendar.days_per_season.map_or(u32::MAX, |x| self.calendar.first_day + x - 1);
+                ui.label("Day");
+                ui.add(egui::DragValue::new(&mut date.day).range(self.calendar.first_day..=last_day).clamp_existing_to_range(false));
+                ui.end_row();
+            };
+            if self.season_node.is_some() && b_date_editable {
+                ui.label("Season");
+                if self.calendar.season_names.is_empty() {
+                    ui.add(egui::DragValue::new(&mut date.season)).on_hover_text("Seasons after the first, the calendar table has no season names");
+                } else {
+                    egui::ComboBox::from_id_salt("time_season")
+                        .selected_text(self.calendar.season_name(date.season))
+                        .show_ui(ui, |ui| {
+                            for (idx, name) in self.calendar.season_names.iter().enumerate() {
+                                ui.selectable_value(&mut date.season, idx, name);
+                            };
+                        });
+                };
+                ui.end_row();
+            };
+            if self.year_node.is_some() && b_date_editable {
+                ui.label("Year");
+                ui.add(egui::DragValue::new(&mut date.year).range(self.calendar.first_year..=i64::MAX).clamp_existing_to_range(false));
+                ui.end_row();
+            };
+            if let Some(age) = self.age.as_mut() {
+                ui.label("Age");
+                b_changed |= match age {
+                    StatValue::Integer(x) => ui.add(egui::DragValue::new(x).range(0..=i64::MAX)).changed(),
+                    StatValue::Decimal(x) => ui.add(egui::DragValue::new(x).speed(0.1).range(0.0..=f64::MAX)).changed(),
+                };
+                ui.end_row();
+            };
+        });
+        if date != self.date {
+            self.shift_to(date);
+            b_changed = true;
+        };
+
+        if self.b_has_date() && !b_date_editable {
+            ui.label("Set DaysPerSeason and SeasonNames in the calendar table to edit the date, so the total days counter moves with it.");
+        };
+        if let (true, Some(days_per_season), Some(days_per_year)) = (self.b_has_date(), self.calendar.days_per_season, self.calendar.days_per_year()) {
+            let days_per_season = days_per_season as i64;
+            ui.horizontal(|ui| {
+                for (label, days) in [("-1 year", -days_per_year), ("-1 season", -days_per_season), ("-1 day", -1),
+                    ("+1 day", 1), ("+1 season", days_per_season), ("+1 year", days_per_year)]
+                {
+                    if ui.button(label).clicked() {
+                        self.shift_by_days(days);
+                        b_changed = true;
+                    };
+                };
+            });
+        };
+        if self.b_has_date() && b_date_editable && self.age_node.is_some() {
+            let mut b_age_with_time = !self.b_age_fixed;
+            ui.checkbox(&mut b_age_with_time, "Age with time").on_hover_text("Changing the year changes the age too");
+            self.b_age_fixed = !b_age_with_time;
+        };
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        match self.season_format {
+            SeasonFormat::Name if self.season_node.is_some() && self.date.season >= self.calendar.season_names.len() =>
+                vec![format!("season {} has no name in the calendar table", self.date.season)],
+            _ => Vec::new(),
+        }
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        if let Some(node) = self.day_node {
+            write_node_if_changed(sm, node, &self.date.day);
+        };
+        if let Some(node) = self.season_node {
+            match self.season_format {
+                SeasonFormat::Index => {write_node_if_changed(sm, node, &(self.date.season as i64 + self.calendar.season_index_base));},
+                SeasonFormat::Name => {write_node_if_changed(sm, node, &self.calendar.season_name(self.date.season));},
+            };
+        };
+        if let Some(node) = self.year_node {
+            write_node_if_changed(sm, node, &self.date.year);
+        };
+        if let (Some(node), Some(total_days)) = (self.total_days_node, self.total_days) {
+            write_node_if_changed(sm, node, &total_days);
+        };
+        if let (Some(node), Some(age)) = (self.age_node, self.age) {
+            write_node_if_changed(sm, node, &age);
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for TimeSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if events.iter().any(|x| x.is_structural()) {
+            self.locate_nodes(sm);
+        } else if !events_touch(events, &self.tracked_nodes()) {
+            return;
+        };
+        // values that don't parse keep the last model shown
+        let _ = self.load(sm, lm);
+    }
+}
+
+
+/// A number or true/false value in a relationship entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RelationshipValue {
+    Number(StatValue),
+    Flag(bool),
+}
+
+struct RelationshipField {
+    node: xot::Node,
+    /// element name, or its parent's name for serialized types, e.g. value for value/int
+    name: String,
+    value: RelationshipValue,
+}
+
+struct RelationshipEntry {
+    npc_id: String,
+    fields: Vec<RelationshipField>,
+}
+
+/// # RelationshipsSection
+/// Villager relationship entries, found in the lists the save survey puts under Relationships.
+/// Each entry is identified by its key (dictionary items) or an id/name value, shown by name if the user's NPC lookup table has it.
+/// Only numbers listed in the relationship fields table are limited or maxed.
+#[derive(Default)]
+pub struct RelationshipsSection {
+    list_nodes: Vec<xot::Node>,
+    entries: Vec<RelationshipEntry>,
+    name_filter: String,
+}
+
+impl RelationshipsSection {
+
+    /// Id of the NPC a relationship entry is for, from its key or a value named like an id.
+    fn find_npc_id(xtree: &xot::Xot, item: xot::Node) -> Option<(xot::Node, String)> {
+        let leaf_text = |node: xot::Node| -> Option<String> {
+            if xtree.children(node).any(|x| xtree.is_element(x)) {return None};
+            xtree.text_content_str(node).map(|x| x.trim().to_string()).filter(|x| !x.is_empty())
+        };
+        if let Some(key_node) = SaveDataManager::get_child_node_from_name_xt(xtree, item, "key") {
+            let key_children: Vec<xot::Node> = xtree.children(key_node).filter(|x| xtree.is_element(*x)).collect();
+            return match key_children[..] {
+                [key_value_node] => leaf_text(key_value_node).map(|x| (key_node, x)),
+                _ => leaf_text(key_node).map(|x| (key_node, x)),
+            };
+        };
+        xtree.children(item)
+            .filter(|x| SaveDataManager::get_name_from_node_xt(xtree, *x).is_some_and(|name| {
+                name_words(name).iter().any(|w| w == "id" || w == "name" || w.starts_with("npc"))
+            }))
+            .find_map(|x| leaf_text(x).map(|text| (x, text)))
+    }
+
+    /// Number and true/false values under node, up to a few levels down.
+    fn collect_fields(xtree: &xot::Xot, node: xot::Node, skip_node: xot::Node, depth: usize, fields: &mut Vec<RelationshipField>) {
+        for child in xtree.children(node).filter(|x| xtree.is_element(*x) && *x != skip_node) {
+            if xtree.children(child).any(|x| xtree.is_element(x)) {
+                if depth < 3 {Self::collect_fields(xtree, child, skip_node, depth + 1, fields)};
+                continue;
+            };
+            let Some(text) = xtree.text_content_str(child).map(|x| x.trim()) else {continue};
+            let value = if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false") {
+                RelationshipValue::Flag(text.eq_ignore_ascii_case("true"))
+            } else if let Ok(x) = text.parse::<StatValue>() {
+                RelationshipValue::Number(x)
+            } else {
+                continue;
+            };
+            let el_name = SaveDataManager::get_name_from_node_xt(xtree, child).unwrap_or_default();
+            let name = match el_name {
+                "int" | "long" | "short" | "byte" | "float" | "double" | "decimal" | "boolean" | "bool" =>
+                    SaveDataManager::get_name_from_node_xt(xtree, node).unwrap_or(el_name),
+                _ => el_name,
+            };
+            fields.push(RelationshipField { node: child, name: name.to_string(), value });
+        };
+    }
+
+    fn locate_lists(&mut self, sm: &SaveDataManager) {
+        let mut survey = SaveSurvey::default();
+        survey.refresh(sm);
+        self.list_nodes = survey.entries_in(SurveyCategory::Relationships)
+            .filter(|x| matches!(x.shape, NodeShape::List { b_item_values: false, .. }))
+            .map(|x| x.node)
+            .collect();
+    }
+
+    fn field_nodes(&self) -> Vec<xot::Node> {
+        self.entries.iter().flat_map(|x| x.fields.iter().map(|field| field.node)).collect()
+    }
+}
+
+impl SaveSection for RelationshipsSection {
+
+    fn name(&self) -> &'static str {
+        "Relationships"
+    }
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::Window
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, _sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        self.locate_lists(sm);
+        Ok(())
+    }
+
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        self.entries.clear();
+        for list_node in self.list_nodes.iter() {
+            for item in sm.xtree.children(*list_node).filter(|x| sm.xtree.is_element(*x)) {
+                let Some((id_node, npc_id)) = Self::find_npc_id(&sm.xtree, item) else {continue};
+                let mut fields: Vec<RelationshipField> = Vec::new();
+                Self::collect_fields(&sm.xtree, item, id_node, 0, &mut fields);
+                if fields.is_empty() {continue};
+                self.entries.push(RelationshipEntry { npc_id, fields });
+            };
+        };
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        self.list_nodes.clone()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        if sc.rd.relationship_field_infos.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "relationship fields", &sc.appconfig.filename_saveedit_relationship_fields));
+        };
+        if sc.rd.npc_lookup.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "NPC names", &sc.appconfig.filename_saveedit_npc_lookup));
+        };
+        if self.entries.is_empty() {
+            ui.label("No relationship entries found in this save, see Relationships in the Save overview window.");
+            return;
+        };
+
+        let mut b_changed = false;
+        let b_any_listed = self.entries.iter().flat_map(|x| x.fields.iter())
+            .any(|x| matches!(x.value, RelationshipValue::Number(_)) && sc.rd.get_relationship_field_info(&x.name).is_some());
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.name_filter);
+            if ui.add_enabled(b_any_listed, egui::Button::new("Max all"))
+                .on_hover_text("Every number listed in the relationship fields table to its max, other numbers are left alone")
+                .on_disabled_hover_text("No number here is listed in the relationship fields table")
+                .clicked()
+            {
+                for field in self.entries.iter_mut().flat_map(|x| x.fields.iter_mut()) {
+                    let RelationshipValue::Number(value) = &mut field.value else {continue};
+                    let Some(info) = sc.rd.get_relationship_field_info(&field.name) else {continue};
+                    *value = match value {
+                        StatValue::Integer(_) => StatValue::Integer(info.max.floor() as i64),
+                        StatValue::Decimal(_) => StatValue::Decimal(info.max),
+                    };
+                    b_changed = true;
+                };
+            };
+        });
+        ui.separator();
+
+        let name_filter = self.name_filter.to_lowercase();
+        let mut order: Vec<(String, usize)> = self.entries.iter().enumerate()
+            .map(|(idx, x)| (sc.rd.get_npc_name(&x.npc_id), idx))
+            .filter(|(name, idx)| name.to_lowercase().contains(&name_filter) || self.entries[*idx].npc_id.to_lowercase().contains(&name_filter))
+            .collect();
+        order.sort();
+
+        egui::Grid::new("relationships_grid").striped(true).num_columns(2).show(ui, |ui| {
+            for (npc_name, idx) in order {
+                let entry = &mut self.entries[idx];
+                ui.label(&npc_name).on_hover_text(&entry.npc_id);
+                ui.horizontal_wrapped(|ui| {
+                    for field in entry.fields.iter_mut() {
+                        let info = sc.rd.get_relationship_field_info(&field.name);
+                        let display_name = info.map(|x| x.display_name.as_str()).filter(|x| !x.is_empty()).unwrap_or(&field.name);
+                        ui.push_id(field.node, |ui| {
+                            match (&mut field.value, info) {
+                                (RelationshipValue::Flag(b_value), _) => {
+                                    b_changed |= ui.checkbox(b_value, display_name).changed();
+                                },
+                                (RelationshipValue::Number(value), Some(info)) => {
+                                    ui.label(display_name).on_hover_text(format!("{}\n{}, {} to {}", info.description, field.name, info.min, info.max));
+                                    b_changed |= match value {
+                                        StatValue::Integer(x) => ui.add(egui::DragValue::new(x)
+                                            .range(info.min.ceil() as i64..=info.max.floor() as i64).clamp_existing_to_range(false)).changed(),
+                                        StatValue::Decimal(x) => ui.add(egui::DragValue::new(x).speed(0.1)
+                                            .range(info.min..=info.max).clamp_existing_to_range(false)).changed(),
+                                    };
+                                },
+                                (RelationshipValue::Number(value), None) => {
+                                    ui.label(display_name);
+                                    b_changed |= match value {
+                                        StatValue::Integer(x) => ui.add(egui::DragValue::new(x)).changed(),
+                                        StatValue::Decimal(x) => ui.add(egui::DragValue::new(x).speed(0.1)).changed(),
+                                    };
+                                },
+                            };
+                        });
+                    };
+                });
+                ui.end_row();
+            };
+        });
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        for field in self.entries.iter().flat_map(|x| x.fields.iter()) {
+            match field.value {
+                RelationshipValue::Number(value) => {write_node_if_changed(sm, field.node, &value);},
+                RelationshipValue::Flag(b_value) => {
+                    let text = sm.xtree.text_content_str(field.node).unwrap_or_default().trim().to_string();
+                    if text.eq_ignore_ascii_case("true") == b_value {continue};
+                    sm.set_node_text(field.node, bool_text_like(b_value, &text).to_string());
+                },
+            };
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for RelationshipsSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if events.iter().any(|x| x.is_structural()) {
+            self.locate_lists(sm);
+        } else if !events_touch(events, &self.field_nodes()) {
+            return;
+        };
+        let _ = self.load(sm, lm);
+    }
+}
+
+
+/// Whether a plot is watered, as a true/false flag or a water level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WaterValue {
+    Flag(bool),
+    Level(StatValue),
+}
+
+struct FarmPlot {
+    plot_node: xot::Node,
+    crop_node: xot::Node,
+    crop_uid: i32,
+    growth: Option<(xot::Node, StatValue)>,
+    water: Option<(xot::Node, WaterValue)>,
+    quality: Option<(xot::Node, StatValue)>,
+}
+
+/// # FarmSection
+/// Planted farm plots, the children of the lists at the plot paths in the farm table in saveedit_data.
+/// The crop is named from the item data. Growth stages aren't known, so growth is only edited as a number.
+/// Crops are only changed, and "Water all" only offered, when every child of the lists is a plot the table describes.
+#[derive(Default)]
+pub struct FarmSection {
+    farm: FarmInfo,
+    /// lists of plots, one per plot path found
+    list_nodes: Vec<xot::Node>,
+    plots: Vec<FarmPlot>,
+    empty_plot_count: usize,
+    /// every child of the lists has a crop value and every value parses
+    b_layout_known: bool,
+    /// plot paths matching more than one node, those aren't searched
+    path_problems: Vec<String>,
+    /// values found that don't parse, those plots or values are left alone
+    problems: Vec<String>,
+}
+
+impl FarmSection {
+
+    fn locate_lists(&mut self, sm: &SaveDataManager) {
+        self.list_nodes.clear();
+        self.path_problems.clear();
+        for path in self.farm.plot_paths.iter() {
+            let nodes = match sm.query(path) {
+                Ok(x) => x,
+                Err(e) => {
+                    self.path_problems.push(format!("{}: {}", path, e));
+                    continue;
+                },
+            };
+            match nodes[..] {
+                // not every save has every list yet
+                [] => {},
+                [node] => {if !self.list_nodes.contains(&node) {self.list_nodes.push(node)}},
+                _ => self.path_problems.push(format!("{} matches {} nodes", path, nodes.len())),
+            };
+        };
+    }
+
+    /// Child of node with the exact element name, and the leaf holding its value, e.g. the int of Growth/int.
+    fn find_field(xtree: &xot::Xot, node: xot::Node, name: Option<&str>) -> Option<xot::Node> {
+        let name = name?;
+        let field_node = xtree.children(node)
+            .find(|x| xtree.is_element(*x) && SaveDataManager::get_name_from_node_xt(xtree, *x) == Some(name))?;
+        let field_children: Vec<xot::Node> = xtree.children(field_node).filter(|x| xtree.is_element(*x)).collect();
+        match field_children[..] {
+            [] => Some(field_node),
+            [value_node] if !xtree.children(value_node).any(|x| xtree.is_element(x)) => Some(value_node),
+            _ => None,
+        }
+    }
+
+    fn load_plot(&mut self, sm: &SaveDataManager, plot_node: xot::Node, crop_node: xot::Node) {
+        let text_of = |node: xot::Node| sm.xtree.text_content_str(node).unwrap_or_default().trim().to_string();
+        let crop_text = text_of(crop_node);
+        let Ok(crop_uid) = crop_text.parse::<i32>() else {
+            self.problems.push(format!("{} {:?} is not an item uid", SaveDataManager::get_node_path_xt(&sm.xtree, crop_node), crop_text));
+            return;
+        };
+        if self.farm.empty_crop_values.contains(&crop_uid) {
+            self.empty_plot_count += 1;
+            return;
+        };
+
+        let problems = &mut self.problems;
+        let mut number_field = |name: Option<&str>| -> Option<(xot::Node, StatValue)> {
+            let node = Self::find_field(&sm.xtree, plot_node, name)?;
+            let text = text_of(node);
+            match text.parse::<StatValue>() {
+                Ok(x) => Some((node, x)),
+                Err(_) => {
+                    problems.push(format!("{} {:?} is not a number", SaveDataManager::get_node_path_xt(&sm.xtree, node), text));
+                    None
+                },
+            }
+        };
+        let growth = number_field(self.farm.growth_field.as_deref());
+        let quality = number_field(self.farm.quality_field.as_deref());
+        let water = Self::find_field(&sm.xtree, plot_node, self.farm.water_field.as_deref()).and_then(|node| {
+            let text = text_of(node);
+            if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false") {
+                return Some((node, WaterValue::Flag(text.eq_ignore_ascii_case("true"))));
+            };
+            match text.parse::<StatValue>() {
+                Ok(x) => Some((node, WaterValue::Level(x))),
+                Err(_) => {
+                    self.problems.push(format!("{} {:?} is not true/false or a number", SaveDataManager::get_node_path_xt(&sm.xtree, node), text));
+                    None
+                },
+            }
+        });
+        self.plots.push(FarmPlot { plot_node, crop_node, crop_uid, growth, water, quality });
+    }
+
+    /// A plot can be watered if it stores a flag, or a number and the farm table gives the full water value.
+    fn b_can_water(&self, plot: &FarmPlot) -> bool {
+        match plot.water {
+            Some((_, WaterValue::Flag(_))) => true,
+            Some((_, WaterValue::Level(_))) => self.farm.water_full.is_some(),
+            None => false,
+        }
+    }
+
+    fn field_nodes(&self) -> Vec<xot::Node> {
+        self.plots.iter()
+            .flat_map(|x| [Some(x.crop_node), x.growth.map(|y| y.0), x.water.map(|y| y.0), x.quality.map(|y| y.0)])
+            .flatten()
+            .collect()
+    }
+}
+
+/// Sets value to a whole or decimal number, keeping its kind.
+fn set_stat_value(value: &mut StatValue, number: f64) {
+    *value = match value {
+        StatValue::Integer(_) => StatValue::Integer(number.round() as i64),
+        StatValue::Decimal(_) => StatValue::Decimal(number),
+    };
+}
+
+impl SaveSection for FarmSection {
+
+    fn name(&self) -> &'static str {
+        "Farm"
+    }
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::Window
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        self.farm = sc.rd.farm.clone();
+        self.locate_lists(sm);
+        Ok(())
+    }
+
+    /// Plots and values that don't parse are reported in the window rather than failing the save,
+    /// and turn off the edits that touch every plot.
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        self.plots.clear();
+        self.empty_plot_count = 0;
+        self.problems.clear();
+        let mut unknown_count: usize = 0;
+        let plot_nodes: Vec<xot::Node> = self.list_nodes.iter()
+            .flat_map(|x| sm.xtree.children(*x))
+            .filter(|x| sm.xtree.is_element(*x))
+            .collect();
+        for plot_node in plot_nodes {
+            match Self::find_field(&sm.xtree, plot_node, self.farm.crop_field.as_deref()) {
+                Some(crop_node) => self.load_plot(sm, plot_node, crop_node),
+                None => unknown_count += 1,
+            };
+        };
+        if unknown_count > 0 && let Some(crop_field) = self.farm.crop_field.as_ref() {
+            self.problems.push(format!("{} entries in the plot lists have no {} value", unknown_count, crop_field));
+        };
+        self.b_layout_known = self.farm.crop_field.is_some() && unknown_count == 0
+            && self.path_problems.is_empty() && self.problems.is_empty();
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        self.list_nodes.clone()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        if sc.rd.farm.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "farm", &sc.appconfig.filename_saveedit_farm));
+        };
+        for problem in self.path_problems.iter().chain(self.problems.iter()) {
+            ui.colored_label(ui.visuals().warn_fg_color, problem).on_hover_text("Not edited here");
+        };
+        if self.plots.is_empty() {
+            ui.label(format!("No planted plots found in this save ({} empty), set the plot paths and fields in the farm table (see README).", self.empty_plot_count));
+            return;
+        };
+
+        let mut b_changed = false;
+        let b_any_water = self.b_layout_known && self.plots.iter().any(|x| self.b_can_water(x));
+        ui.horizontal(|ui| {
+            ui.label(format!("{} planted plots, {} empty", self.plots.len(), self.empty_plot_count));
+            if ui.add_enabled(b_any_water, egui::Button::new("Water all"))
+                .on_disabled_hover_text("Needs every plot list entry to be a plot, and WaterFull in the farm table for water levels")
+                .clicked()
+            {
+                let water_full = self.farm.water_full;
+                for (_, water) in self.plots.iter_mut().filter_map(|x| x.water.as_mut()) {
+                    match (water, water_full) {
+                        (WaterValue::Flag(b_value), _) => *b_value = true,
+                        (WaterValue::Level(value), Some(water_full)) => set_stat_value(value, water_full),
+                        (WaterValue::Level(_), None) => {},
+                    };
+                };
+                b_changed = true;
+            };
+        });
+        if !self.b_layout_known {
+            ui.colored_label(ui.visuals().warn_fg_color, "Farm layout not recognised, crops can't be changed")
+                .on_hover_text("Check the plot paths and fields in the farm table against the Save overview window (see README)");
+        };
+        ui.separator();
+
+        let seed_uids = sc.lm.get_seed_uids();
+        let item_name = |uid: i32| -> String {
+            match sc.lm.full_item_lookup.get(&uid) {
+                Some(x) => x.name.clone(),
+                None => format!("Unknown item {}", uid),
+            }
+        };
+        let growth_stage_base = self.farm.growth_stage_base;
+        // growth can't go below the first stage, or below zero when that isn't known
+        let growth_min = growth_stage_base.unwrap_or(0);
+        let b_layout_known = self.b_layout_known;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("farm_grid").striped(true).num_columns(5).show(ui, |ui| {
+                ui.strong("Crop");
+                ui.strong("Preset");
+                ui.strong("Growth");
+                ui.strong("Water");
+                ui.strong("Quality");
+                ui.end_row();
+                for plot in self.plots.iter_mut() {
+                    ui.push_id(plot.plot_node, |ui| {
+                        // a new crop resets the growth, which needs the first stage
+                        let b_crop_editable = b_layout_known && (plot.growth.is_none() || growth_stage_base.is_some());
+                        let mut crop_uid = plot.crop_uid;
+                        ui.add_enabled_ui(b_crop_editable, |ui| {
+                            egui::ComboBox::from_id_salt("farm_crop")
+                                .selected_text(item_name(crop_uid))
+                                .height(400.0)
+                                .show_ui(ui, |ui| {
+                                    for uid in seed_uids.iter() {
+                                        ui.selectable_value(&mut crop_uid, *uid, item_name(*uid));
+                                    };
+                                })
+                                .response.on_hover_text(format!("{}\nuid {}", SaveDataManager::get_node_path_xt(&sm.xtree, plot.plot_node), plot.crop_uid));
+                        });
+                        if crop_uid != plot.crop_uid {
+                            // a new crop starts from the first stage
+                            plot.crop_uid = crop_uid;
+                            if let (Some((_, growth)), Some(base)) = (plot.growth.as_mut(), growth_stage_base) {set_stat_value(growth, base as f64)};
+                            b_changed = true;
+                        };
+
+                        match sc.lm.full_item_lookup.get(&plot.crop_uid) {
+                            Some(item) if item.growable_preset_idx >= 0 => {ui.label(format!("Preset {}", item.growable_preset_idx));},
+                            _ => {ui.label("-");},
+                        };
+
+                        match plot.growth.as_mut() {
+                            Some((_, growth)) => {
+                                b_changed |= match growth {
+                                    StatValue::Integer(x) => ui.add(egui::DragValue::new(x)
+                                        .range(growth_min..=i64::MAX).clamp_existing_to_range(false)).changed(),
+                                    StatValue::Decimal(x) => ui.add(egui::DragValue::new(x).speed(0.1)
+                                        .range(growth_min as f64..=f64::MAX).clamp_existing_to_range(false)).changed(),
+                                };
+                            },
+                            None => {ui.label("-");},
+                        };
+
+                        match plot.water.as_mut() {
+                            Some((_, WaterValue::Flag(b_value))) => {b_changed |= ui.checkbox(b_value, "").changed();},
+                            Some((_, WaterValue::Level(StatValue::Integer(x)))) => {b_changed |= ui.add(egui::DragValue::new(x).range(0..=i64::MAX)).changed();},
+                            Some((_, WaterValue::Level(StatValue::Decimal(x)))) => {b_changed |= ui.add(egui::DragValue::new(x).speed(0.1).range(0.0..=f64::MAX)).changed();},
+                            None => {ui.label("-");},
+                        };
+
+                        match plot.quality.as_mut() {
+                            Some((_, StatValue::Integer(x))) => {b_changed |= ui.add(egui::DragValue::new(x).range(0..=i64::MAX)).changed();},
+                            Some((_, StatValue::Decimal(x))) => {b_changed |= ui.add(egui::DragValue::new(x).speed(0.1).range(0.0..=f64::MAX)).changed();},
+                            None => {ui.label("-");},
+                        };
+                    });
+                    ui.end_row();
+                };
+            });
+        });
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        self.plots.iter()
+            .filter(|x| self.farm.empty_crop_values.contains(&x.crop_uid))
+            .map(|x| format!("plot crop {} means an empty plot, not a crop", x.crop_uid))
+            .collect()
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        for plot in self.plots.iter() {
+            write_node_if_changed(sm, plot.crop_node, &plot.crop_uid);
+            if let Some((node, growth)) = plot.growth {
+                write_node_if_changed(sm, node, &growth);
+            };
+            if let Some((node, quality)) = plot.quality {
+                write_node_if_changed(sm, node, &quality);
+            };
+            match plot.water {
+                Some((node, WaterValue::Level(value))) => {write_node_if_changed(sm, node, &value);},
+                Some((node, WaterValue::Flag(b_value))) => {
+                    let text = sm.xtree.text_content_str(node).unwrap_or_default().trim().to_string();
+                    if text.eq_ignore_ascii_case("true") == b_value {continue};
+                    sm.set_node_text(node, bool_text_like(b_value, &text).to_string());
+                },
+                None => {},
+            };
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for FarmSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if events.iter().any(|x| x.is_structural()) {
+            self.locate_lists(sm);
+        } else if !events_touch(events, &self.field_nodes()) && !events_touch(events, &self.list_nodes) {
+            return;
+        };
+        let _ = self.load(sm, lm);
+    }
+}
+
+
+struct KnownEntry {
+    id: i32,
+    label: String,
+    /// listed by the game data, otherwise only in the save
+    b_in_data: bool,
+}
+
+/// One list of known ids in the save, against the entries the game data defines for it.
+struct KnownList {
+    info: KnownListInfo,
+    entries: Vec<KnownEntry>,
+    list_node: Option<xot::Node>,
+    /// element name of the ids in the list, e.g. int, None until the list has had an id
+    value_element: Option<String>,
+    /// ids to mark known in the save
+    known: HashSet<i32>,
+    /// why the path doesn't give a list
+    path_problem: Option<String>,
+    /// why the list found isn't edited
+    problem: Option<String>,
+}
+
+impl KnownList {
+
+    /// Entries from the game data, sorted by label, one per id.
+    fn data_entries(info: &KnownListInfo, lm: &LootManager, arm: &ApothRecipeManager) -> Vec<KnownEntry> {
+        let item_name = |uid: i32| match lm.full_item_lookup.get(&uid) {
+            Some(x) => x.name.clone(),
+            None => format!("Item {}", uid),
+        };
+        let mut entries: Vec<KnownEntry> = match &info.source {
+            KnownListSource::ApothRecipes(key) => arm.all_cures.iter().enumerate()
+                .map(|(idx, recipe)| {
+                    let ailment_name = arm.get_ailment_name(recipe.ailment_id);
+                    let (id, label) = match key {
+                        ApothRecipeKey::ItemID => (recipe.item_id, format!("{} ({})", item_name(recipe.item_id), ailment_name)),
+                        ApothRecipeKey::AilmentID => (recipe.ailment_id, ailment_name),
+                        ApothRecipeKey::Index => (idx as i32, format!("{}: {} ({:?})", ailment_name, item_name(recipe.item_id), recipe.result_type)),
+                    };
+                    KnownEntry { id, label, b_in_data: true }
+                })
+                .collect(),
+            KnownListSource::Items(pickup_type) => match lm.pickup_type_lookup.get(pickup_type) {
+                Some(type_of_pickup) => lm.full_item_lookup.values()
+                    .filter(|x| x.type_of_pickup == *type_of_pickup)
+                    .map(|x| KnownEntry { id: x.uid, label: x.name.clone(), b_in_data: true })
+                    .collect(),
+                None => Vec::new(),
+            },
+        };
+        let mut seen: HashSet<i32> = HashSet::new();
+        entries.retain(|x| seen.insert(x.id));
+        entries.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+        entries
+    }
+
+    /// The single element the path matches, lists not in the save or matching several nodes aren't edited.
+    fn locate(&mut self, sm: &SaveDataManager) {
+        let (list_node, path_problem) = match sm.query(&self.info.path) {
+            Ok(nodes) => match nodes[..] {
+                [] => (None, None),
+                [node] if sm.xtree.is_element(node) => (Some(node), None),
+                _ => (None, Some(format!("{} matches {} nodes, list not edited", self.info.path, nodes.len()))),
+            },
+            Err(e) => (None, Some(format!("{}: {}", self.info.path, e))),
+        };
+        if list_node != self.list_node {self.value_element = None};
+        self.list_node = list_node;
+        self.path_problem = path_problem;
+    }
+
+    /// Id children of the list node, or why it isn't a list of ids.
+    fn read_ids(&self, xtree: &xot::Xot) -> Result<Vec<(xot::Node, i32)>, String> {
+        let Some(list_node) = self.list_node else {return Ok(Vec::new())};
+        xtree.children(list_node)
+            .filter(|x| xtree.is_element(*x))
+            .map(|x| {
+                let text = xtree.text_content_str(x).unwrap_or_default().trim();
+                match text.parse::<i32>() {
+                    Ok(id) if !xtree.children(x).any(|y| xtree.is_element(y)) => Ok((x, id)),
+                    _ => Err(format!("{} {:?} is not an id, list not edited", SaveDataManager::get_node_path_xt(xtree, x), text)),
+                }
+            })
+            .collect()
+    }
+
+    fn load(&mut self, xtree: &xot::Xot) {
+        self.entries.retain(|x| x.b_in_data);
+        self.known.clear();
+        self.problem = None;
+        match self.read_ids(xtree) {
+            Ok(ids) => {
+                if let Some(name) = ids.first().and_then(|x| SaveDataManager::get_name_from_node_xt(xtree, x.0)) {
+                    self.value_element = Some(name.to_string());
+                };
+                for (_, id) in ids {
+                    if !self.entries.iter().any(|x| x.id == id) {
+                        self.entries.push(KnownEntry { id, label: format!("Unknown {}", id), b_in_data: false });
+                    };
+                    self.known.insert(id);
+                };
+            },
+            Err(e) => self.problem = Some(e),
+        };
+    }
+
+    /// Ids can only be added once the list has shown which element holds them.
+    fn b_can_add(&self) -> bool {
+        self.value_element.is_some()
+    }
+
+    /// Removes the ids no longer known and appends the new ones, in entry order.
+    /// Nothing is written if ids would be added to a list that has never held one.
+    fn write_back(&self, sm: &mut SaveDataManager) -> Result<(), String> {
+        let Some(list_node) = self.list_node else {return Ok(())};
+        if self.problem.is_some() {return Ok(())};
+        let ids = self.read_ids(&sm.xtree)?;
+        let b_adding = self.known.iter().any(|id| !ids.iter().any(|x| x.1 == *id));
+        let value_element = match (&self.value_element, b_adding) {
+            (Some(x), _) => x.clone(),
+            (None, false) => String::new(),
+            (None, true) => return Err(format!("{} is empty in this save, ids can't be added to it", self.info.name)),
+        };
+        let mut saved: HashSet<i32> = HashSet::new();
+        for (node, id) in ids {
+            if self.known.contains(&id) && saved.insert(id) {continue};
+            sm.xtree.remove(node).map_err(|e| format!("{}: {}", self.info.name, e))?;
+            sm.changes.push(SaveChangeEvent::NodeRemoved { node, parent: list_node });
+        };
+        for entry in self.entries.iter().filter(|x| self.known.contains(&x.id) && !saved.contains(&x.id)) {
+            let name_id = sm.xtree.add_name(&value_element);
+            let node = sm.xtree.new_element(name_id);
+            let text_node = sm.xtree.new_text(&entry.id.to_string());
+            sm.xtree.append(node, text_node).map_err(|e| format!("{}: {}", self.info.name, e))?;
+            sm.xtree.append(list_node, node).map_err(|e| format!("{}: {}", self.info.name, e))?;
+            sm.changes.push(SaveChangeEvent::NodeInserted { node, parent: list_node });
+        };
+        Ok(())
+    }
+}
+
+/// # KnownRecipesSection
+/// Recipes and discoveries the save marks as known, one list per row of the known recipes table in saveedit_data,
+/// shown against every entry the game data (ApothRecipes.xml, the item files) defines for it.
+#[derive(Default)]
+pub struct KnownRecipesSection {
+    lists: Vec<KnownList>,
+    filter: String,
+}
+
+impl KnownRecipesSection {
+
+    fn list_nodes(&self) -> Vec<xot::Node> {
+        self.lists.iter().filter_map(|x| x.list_node).collect()
+    }
+}
+
+impl SaveSection for KnownRecipesSection {
+
+    fn name(&self) -> &'static str {
+        "Known recipes"
+    }
+
+    fn placement(&self) -> SectionPlacement {
+        SectionPlacement::Window
+    }
+
+    fn locate(&mut self, sm: &SaveDataManager, sc: &SectionContext) -> Result<(), Box<dyn Error>> {
+        // lists not in the save aren't shown as editable, they're never added
+        self.lists = sc.rd.known_lists.iter()
+            .map(|info| KnownList {
+                entries: KnownList::data_entries(info, sc.lm, sc.arm),
+                info: info.clone(),
+                list_node: None,
+                value_element: None,
+                known: HashSet::new(),
+                path_problem: None,
+                problem: None,
+            })
+            .collect();
+        self.lists.iter_mut().for_each(|x| x.locate(sm));
+        Ok(())
+    }
+
+    /// Lists that aren't lists of ids are reported in the window rather than failing the save.
+    fn load(&mut self, sm: &SaveDataManager, _lm: &LootManager) -> Result<(), Box<dyn Error>> {
+        self.lists.iter_mut().for_each(|x| x.load(&sm.xtree));
+        Ok(())
+    }
+
+    fn tracked_nodes(&self) -> Vec<xot::Node> {
+        self.list_nodes()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, sm: &mut SaveDataManager, sc: &SectionContext, error_msg: &mut String) {
+        if self.lists.is_empty() {
+            ui.weak(empty_table_note(sc.appconfig, "known recipes", &sc.appconfig.filename_saveedit_known_recipes));
+            return;
+        };
+
+        let mut b_changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for list in self.lists.iter_mut() {
+                let header = match (list.list_node, &list.path_problem) {
+                    (Some(_), _) => format!("{} ({} of {} known)", list.info.name, list.known.len(), list.entries.len()),
+                    (None, Some(_)) => format!("{} (not edited)", list.info.name),
+                    (None, None) => format!("{} (not in this save)", list.info.name),
+                };
+                egui::CollapsingHeader::new(header).id_salt(&list.info.name).show(ui, |ui| {
+                    let Some(list_node) = list.list_node else {
+                        match &list.path_problem {
+                            Some(problem) => {ui.colored_label(ui.visuals().warn_fg_color, problem);},
+                            None => {ui.label(format!("{} not found, check the known recipes table.", list.info.path));},
+                        };
+                        return;
+                    };
+                    if let Some(problem) = &list.problem {
+                        ui.colored_label(ui.visuals().warn_fg_color, problem);
+                        return;
+                    };
+                    if list.entries.is_empty() {
+                        ui.label("The game data has no entries for this list.");
+                    };
+                    let b_can_add = list.b_can_add();
+                    if !b_can_add {
+                        ui.label("The list is empty in this save, so entries can't be ticked: the element its ids are saved as isn't known.");
+                    };
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(b_can_add, egui::Button::new("Unlock all")).clicked() {
+                            list.known.extend(list.entries.iter().filter(|x| x.b_in_data).map(|x| x.id));
+                            b_changed = true;
+                        };
+                        if ui.button("Reset").on_hover_text("Marks every entry unknown").clicked() {
+                            list.known.clear();
+                            b_changed = true;
+                        };
+                        ui.label(SaveDataManager::get_node_path_xt(&sm.xtree, list_node));
+                    });
+                    for entry in list.entries.iter().filter(|x| x.label.to_lowercase().contains(&filter)) {
+                        let mut b_known = list.known.contains(&entry.id);
+                        let response = ui.add_enabled(b_can_add || b_known, egui::Checkbox::new(&mut b_known, &entry.label))
+                            .on_hover_text(match entry.b_in_data {
+                                true => format!("id {}", entry.id),
+                                false => format!("id {}, in the save but not in the game data", entry.id),
+                            });
+                        if response.changed() {
+                            match b_known {
+                                true => list.known.insert(entry.id),
+                                false => list.known.remove(&entry.id),
+                            };
+                            b_changed = true;
+                        };
+                    };
+                });
+            };
+        });
+
+        if b_changed {
+            validate_and_write_back(self, sm, sc, error_msg);
+        };
+    }
+
+    fn validate(&self, _sc: &SectionContext) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn write_back(&mut self, sm: &mut SaveDataManager, _sc: &SectionContext) -> Result<(), String> {
+        for list in self.lists.iter() {
+            list.write_back(sm)?;
+        };
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl SaveChangeListener for KnownRecipesSection {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, lm: &LootManager, events: &[SaveChangeEvent]) {
+        if events.iter().any(|x| x.is_structural()) {
+            self.lists.iter_mut().for_each(|x| x.locate(sm));
+        } else {
+            let list_nodes = self.list_nodes();
+            let b_touched = events.iter().any(|x| match x {
+                SaveChangeEvent::TextChanged(node) => sm.xtree.parent(*node).is_some_and(|parent| list_nodes.contains(&parent)),
+                _ => false,
+            });
+            if !b_touched {return};
+        };
+        let _ = self.load(sm, lm);
+    }
+}
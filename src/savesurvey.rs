@@ -0,0 +1,183 @@
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::lootitems::LootManager;
+use crate::savechanges::{SaveChangeEvent, SaveChangeListener};
+use crate::savedata::{SaveDataManager, SaveValueType};
+
+/// What a part of the save is probably about, guessed from the words of its element name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum SurveyCategory {
+    Calendar,
+    Relationships,
+    Recipes,
+    Quests,
+    Farm,
+    Other,
+}
+
+impl SurveyCategory {
+
+    /// Starts of the lower case words in element names, e.g. "season" matches "CurrentSeasons".
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Self::Calendar => &["time", "day", "season", "year", "calendar", "date", "age", "clock", "week"],
+            Self::Relationships => &["relationship", "relation", "friend", "affinity", "npc", "villager", "romance", "heart"],
+            Self::Recipes => &["recipe", "known", "discover", "unlock", "learn", "cure"],
+            Self::Quests => &["quest", "flag", "task", "story", "event"],
+            Self::Farm => &["farm", "plot", "crop", "plant", "grow", "field", "seed", "soil"],
+            Self::Other => &[],
+        }
+    }
+
+    pub fn of_name(name: &str) -> Self {
+        let words = name_words(name);
+        Self::iter()
+            .find(|category| category.keywords().iter().any(|k| words.iter().any(|w| w.starts_with(k))))
+            .unwrap_or(Self::Other)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Calendar => "Calendar and time",
+            Self::Relationships => "Relationships",
+            Self::Recipes => "Recipes and discoveries",
+            Self::Quests => "Quests and flags",
+            Self::Farm => "Farm",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// Lower case words of an element name split at camel case, digits and underscores, e.g. "NPC", "relations" for "NPCRelations_2".
+pub fn name_words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut b_prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphabetic() {
+            if !word.is_empty() {words.push(std::mem::take(&mut word))};
+            b_prev_lower = false;
+            continue;
+        };
+        if c.is_uppercase() && b_prev_lower {words.push(std::mem::take(&mut word))};
+        word.extend(c.to_lowercase());
+        b_prev_lower = c.is_lowercase();
+    };
+    if !word.is_empty() {words.push(word)};
+    words
+}
+
+/// How a node's content is laid out, which decides the editor used for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeShape {
+    /// no children and no text
+    Empty,
+    /// leaf element with text
+    Value(SaveValueType),
+    /// children with the same name, e.g. ToolLevelling/ToolLevel
+    List { item_name: String, len: usize, b_item_values: bool },
+    /// differently named children
+    Record { field_count: usize },
+}
+
+impl NodeShape {
+
+    pub fn of_node(xtree: &xot::Xot, node: xot::Node) -> Self {
+        let children: Vec<xot::Node> = xtree.children(node).filter(|x| xtree.is_element(*x)).collect();
+        let Some(first_child) = children.first() else {
+            return match xtree.first_child(node).is_some_and(|x| xtree.is_text(x)) {
+                true => Self::Value(SaveValueType::infer(xtree, node)),
+                false => Self::Empty,
+            };
+        };
+
+        let first_name = SaveDataManager::get_name_from_node_xt(xtree, *first_child).unwrap_or_default();
+        let b_same_names = children.iter().all(|x| SaveDataManager::get_name_from_node_xt(xtree, *x) == Some(first_name));
+        // a single child is a list item if it's a serialized type or named after the parent, e.g. ToolLevelling/ToolLevel
+        let parent_name = SaveDataManager::get_name_from_node_xt(xtree, node).unwrap_or_default().to_lowercase();
+        let b_list_item_name = ["int", "long", "float", "string", "boolean", "guid", "item"].contains(&first_name)
+            || parent_name.contains(&first_name.to_lowercase());
+        if b_same_names && (children.len() > 1 || b_list_item_name) {
+            return Self::List {
+                item_name: first_name.to_string(),
+                len: children.len(),
+                b_item_values: !xtree.children(*first_child).any(|x| xtree.is_element(x)),
+            };
+        };
+        Self::Record { field_count: children.len() }
+    }
+
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Empty => "empty".to_string(),
+            Self::Value(value_type) => value_type.label().to_string(),
+            Self::List { item_name, len, .. } => format!("{} x {}", len, item_name),
+            Self::Record { field_count: 1 } => "1 field".to_string(),
+            Self::Record { field_count } => format!("{} fields", field_count),
+        }
+    }
+}
+
+/// # SurveyEntry
+/// One top level part of the save, a child of PlayerData or of the document element.
+#[derive(Debug, Clone)]
+pub struct SurveyEntry {
+    pub node: xot::Node,
+    pub name: String,
+    /// absolute save query path, e.g. /SaveGame/PlayerData/ToolLevelling
+    pub path: String,
+    pub shape: NodeShape,
+    pub category: SurveyCategory,
+    /// edited by an editor of its own, i.e. its node is pinned
+    pub b_has_editor: bool,
+}
+
+/// # SaveSurvey
+/// The top level parts of a loaded save, looked up again when the save's structure changes.
+#[derive(Debug, Default)]
+pub struct SaveSurvey {
+    pub entries: Vec<SurveyEntry>,
+}
+
+impl SaveSurvey {
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// PlayerData's children first, then the document element's other children.
+    pub fn refresh(&mut self, sm: &SaveDataManager) {
+        self.entries.clear();
+        let parents = [sm.playerdata_node, sm.doc_el];
+        for parent in parents.into_iter().flatten() {
+            for child in sm.xtree.children(parent) {
+                if !sm.xtree.is_element(child) || Some(child) == sm.playerdata_node {continue};
+                let name = sm.get_name_from_node(child).unwrap_or_default().to_string();
+                self.entries.push(SurveyEntry {
+                    node: child,
+                    path: SaveDataManager::get_node_query_path_xt(&sm.xtree, child),
+                    shape: NodeShape::of_node(&sm.xtree, child),
+                    category: SurveyCategory::of_name(&name),
+                    b_has_editor: sm.changes.is_pinned(child),
+                    name,
+                });
+            };
+        };
+    }
+
+    pub fn entries_in(&self, category: SurveyCategory) -> impl Iterator<Item = &SurveyEntry> {
+        self.entries.iter().filter(move |x| x.category == category)
+    }
+}
+
+impl SaveChangeListener for SaveSurvey {
+
+    fn on_save_changes(&mut self, sm: &SaveDataManager, _lm: &LootManager, events: &[SaveChangeEvent]) {
+        if self.entries.is_empty() || !events.iter().any(|x| x.is_structural()) {return};
+        self.refresh(sm);
+    }
+}